@@ -0,0 +1,367 @@
+//! Renders release notes from conventional commits.
+use crate::commitlint::ConventionalCommit;
+use crate::git::LogItem;
+use serde::Deserialize;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Format {
+    /// https://keepachangelog.com
+    KeepAChangelog,
+    /// The format of conventional-changelog's angular preset.
+    Conventional,
+}
+
+/// A heading in the changelog and the commit types listed under it.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Section {
+    pub title: String,
+    pub types: Vec<String>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ChangelogConfig {
+    pub format: Format,
+    /// When `None`, the default sections for the format are used.
+    pub sections: Option<Vec<Section>>,
+    /// A link to a commit, where `{hash}` is replaced by the full hash.
+    pub commit_url: Option<String>,
+}
+
+/// The release that the commits are listed under.
+#[derive(Debug, Clone, Default)]
+pub struct Release {
+    /// `None` renders an "Unreleased" heading.
+    pub version: Option<String>,
+    /// As `YYYY-MM-DD`.
+    pub date: Option<String>,
+}
+
+impl ChangelogConfig {
+    pub fn sections(&self) -> Vec<Section> {
+        if let Some(ref sections) = self.sections {
+            return sections.clone();
+        }
+
+        let section = |title: &str, types: &[&str]| Section {
+            title: title.to_string(),
+            types: types.iter().map(|ty| ty.to_string()).collect(),
+        };
+
+        match self.format {
+            Format::KeepAChangelog => vec![
+                section("Added", &["feat"]),
+                section("Changed", &["perf", "refactor", "enhance", "improvement"]),
+                section("Fixed", &["fix"]),
+                section("Reverted", &["revert"]),
+            ],
+            Format::Conventional => vec![
+                section("Features", &["feat"]),
+                section("Improvements", &["enhance", "improvement"]),
+                section("Bug Fixes", &["fix"]),
+                section("Performance Improvements", &["perf"]),
+                section("Reverts", &["revert"]),
+            ],
+        }
+    }
+}
+
+impl Default for ChangelogConfig {
+    fn default() -> Self {
+        ChangelogConfig {
+            format: Format::KeepAChangelog,
+            sections: None,
+            commit_url: None,
+        }
+    }
+}
+
+/// Renders one release. Commits that aren't conventional, or whose type isn't in any
+/// section, are left out unless they're breaking changes.
+pub fn render(logs: &[LogItem], release: &Release, config: &ChangelogConfig) -> String {
+    let bullet = match config.format {
+        Format::KeepAChangelog => "-",
+        Format::Conventional => "*",
+    };
+
+    let commits: Vec<(&LogItem, ConventionalCommit)> = logs
+        .iter()
+        .filter_map(|log| Some((log, log.as_conventional().ok()?)))
+        .collect();
+
+    let mut out = heading(release, config.format);
+
+    let breaking: Vec<String> = commits
+        .iter()
+        .filter(|(_, commit)| commit.breaking)
+        .map(|(log, commit)| {
+            let description = commit
+                .footers
+                .iter()
+                .find(|footer| footer.is_breaking_change())
+                .map_or(commit.subject, |footer| footer.value);
+            entry(bullet, log, commit.scope, description, config)
+        })
+        .collect();
+    push_section(&mut out, "⚠ BREAKING CHANGES", &breaking);
+
+    for section in config.sections() {
+        let entries: Vec<String> = commits
+            .iter()
+            .filter(|(_, commit)| section.types.iter().any(|ty| ty == commit.ty))
+            .map(|(log, commit)| entry(bullet, log, commit.scope, commit.subject, config))
+            .collect();
+        push_section(&mut out, &section.title, &entries);
+    }
+
+    out
+}
+
+fn heading(release: &Release, format: Format) -> String {
+    let version = release.version.as_deref().unwrap_or("Unreleased");
+    match (format, &release.date) {
+        (Format::KeepAChangelog, Some(date)) => format!("## [{}] - {}\n", version, date),
+        (Format::KeepAChangelog, None) => format!("## [{}]\n", version),
+        (Format::Conventional, Some(date)) => format!("## {} ({})\n", version, date),
+        (Format::Conventional, None) => format!("## {}\n", version),
+    }
+}
+
+fn entry(
+    bullet: &str,
+    log: &LogItem,
+    scope: Option<&str>,
+    description: &str,
+    config: &ChangelogConfig,
+) -> String {
+    let short = &log.commit[..log.commit.len().min(7)];
+    let hash = match config.commit_url {
+        Some(ref url) => format!("[{}]({})", short, url.replace("{hash}", &log.commit)),
+        None => short.to_string(),
+    };
+
+    // Multi-line breaking change descriptions are indented under their bullet.
+    let description = description.lines().collect::<Vec<_>>().join("\n  ");
+    match scope {
+        Some(scope) => format!("{} **{}:** {} ({})", bullet, scope, description, hash),
+        None => format!("{} {} ({})", bullet, description, hash),
+    }
+}
+
+fn push_section(out: &mut String, title: &str, entries: &[String]) {
+    if entries.is_empty() {
+        return;
+    }
+
+    out.push_str(&format!("\n### {}\n\n", title));
+    for entry in entries {
+        out.push_str(entry);
+        out.push('\n');
+    }
+}
+
+/// Inserts `release` above the newest release in an existing changelog, keeping its title
+/// and introduction. An empty `existing` gets a title for the format.
+pub fn prepend(existing: &str, release: &str, format: Format) -> String {
+    if existing.trim().is_empty() {
+        let intro = match format {
+            Format::KeepAChangelog => {
+                "# Changelog\n\nAll notable changes to this project will be documented in this file.\n\n"
+            }
+            Format::Conventional => "# Changelog\n\n",
+        };
+        return format!("{}{}", intro, release);
+    }
+
+    let mut offset = 0;
+    for line in existing.split_inclusive('\n') {
+        if line.starts_with("## ") {
+            return format!(
+                "{}{}\n{}",
+                &existing[..offset],
+                release,
+                &existing[offset..]
+            );
+        }
+        offset += line.len();
+    }
+
+    format!("{}\n\n{}", existing.trim_end(), release)
+}
+
+/// Guesses the commit URL of a GitHub/GitLab style remote, e.g. `git@host:owner/repo.git`.
+pub fn commit_url_for_remote(remote: &str) -> Option<String> {
+    let remote = remote.trim();
+    let path = if let Some(rest) = remote
+        .strip_prefix("https://")
+        .or_else(|| remote.strip_prefix("http://"))
+    {
+        // Drop credentials, e.g. `https://token@host/owner/repo`
+        rest.rsplit('@').next()?.to_string()
+    } else if let Some(rest) = remote.strip_prefix("ssh://") {
+        let rest = rest.rsplit('@').next()?;
+        // Drop the port, which the web UI doesn't use.
+        let (host, path) = rest.split_once('/')?;
+        format!("{}/{}", host.split(':').next()?, path)
+    } else {
+        let (user_host, path) = remote.split_once(':')?;
+        let host = user_host.rsplit('@').next()?;
+        format!("{}/{}", host, path)
+    };
+
+    let path = path.trim_end_matches('/').trim_end_matches(".git");
+    if path.split('/').count() < 3 {
+        return None;
+    }
+
+    Some(format!("https://{}/commit/{{hash}}", path))
+}
+
+/// Formats seconds since the Unix epoch as a UTC `YYYY-MM-DD` date.
+pub fn date_from_epoch(epoch_secs: i64) -> String {
+    // From Howard Hinnant's `civil_from_days`.
+    let days = epoch_secs.div_euclid(86_400) + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "keep-a-changelog" => Ok(Format::KeepAChangelog),
+            "conventional" => Ok(Format::Conventional),
+            _ => Err(format!(
+                "unknown format '{}', expected 'keep-a-changelog' or 'conventional'",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Format::KeepAChangelog => write!(f, "keep-a-changelog"),
+            Format::Conventional => write!(f, "conventional"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        commit_url_for_remote, date_from_epoch, prepend, render, ChangelogConfig, Format, Release,
+    };
+    use crate::git::LogItem;
+
+    fn log(commit: &str, message: &str) -> LogItem {
+        LogItem {
+            commit: commit.repeat(40),
+            epoch_secs: 0,
+            message: message.to_string(),
+            files: vec![],
+        }
+    }
+
+    fn logs() -> Vec<LogItem> {
+        vec![
+            log(
+                "a",
+                "feat(api)!: drop v1\n\nBREAKING CHANGE: v1 clients must upgrade",
+            ),
+            log("b", "fix: handle empty input"),
+            log("c", "chore: update deps"),
+            log("d", "not conventional"),
+        ]
+    }
+
+    #[test]
+    fn keep_a_changelog() {
+        let config = ChangelogConfig {
+            commit_url: Some("https://example.com/c/{hash}".into()),
+            ..Default::default()
+        };
+        let release = Release {
+            version: Some("2.0.0".into()),
+            date: Some("2024-05-01".into()),
+        };
+        let link = |c: &str| format!("[{}](https://example.com/c/{})", c.repeat(7), c.repeat(40));
+
+        assert_eq!(
+            render(&logs(), &release, &config),
+            format!(
+                "## [2.0.0] - 2024-05-01\n\n\
+                 ### ⚠ BREAKING CHANGES\n\n\
+                 - **api:** v1 clients must upgrade ({a})\n\n\
+                 ### Added\n\n\
+                 - **api:** drop v1 ({a})\n\n\
+                 ### Fixed\n\n\
+                 - handle empty input ({b})\n",
+                a = link("a"),
+                b = link("b"),
+            )
+        );
+    }
+
+    #[test]
+    fn conventional_unreleased() {
+        let config = ChangelogConfig {
+            format: Format::Conventional,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            render(&logs()[1..], &Release::default(), &config),
+            "## Unreleased\n\n### Bug Fixes\n\n* handle empty input (bbbbbbb)\n"
+        );
+    }
+
+    #[test]
+    fn prepends_above_the_last_release() {
+        let existing = "# Changelog\n\nIntro.\n\n## [1.0.0]\n\n- old\n";
+        assert_eq!(
+            prepend(existing, "## [2.0.0]\n\n- new\n", Format::KeepAChangelog),
+            "# Changelog\n\nIntro.\n\n## [2.0.0]\n\n- new\n\n## [1.0.0]\n\n- old\n"
+        );
+        assert_eq!(
+            prepend("", "## 1.0.0\n", Format::Conventional),
+            "# Changelog\n\n## 1.0.0\n"
+        );
+    }
+
+    #[test]
+    fn remotes_and_dates() {
+        let expected = Some("https://github.com/brigand/glint/commit/{hash}".to_string());
+        assert_eq!(
+            commit_url_for_remote("git@github.com:brigand/glint.git"),
+            expected
+        );
+        assert_eq!(
+            commit_url_for_remote("https://github.com/brigand/glint"),
+            expected
+        );
+        assert_eq!(
+            commit_url_for_remote("ssh://git@github.com:22/brigand/glint.git"),
+            expected
+        );
+        assert_eq!(commit_url_for_remote("/srv/repo.git"), None);
+
+        assert_eq!(date_from_epoch(0), "1970-01-01");
+        assert_eq!(date_from_epoch(1568585467), "2019-09-15");
+        assert_eq!(date_from_epoch(951_782_400), "2000-02-29");
+    }
+}
@@ -1,7 +1,10 @@
+use glint::changelog::Format;
+use glint::commitlint::Footer;
+use std::path::PathBuf;
 use structopt::clap::AppSettings;
 use structopt::StructOpt;
 
-#[derive(StructOpt)]
+#[derive(StructOpt, Default, Clone)]
 pub struct Commit {
     /// Sets the 'type' component of the commit (optional; otherwise interactive prompt)
     #[structopt(short, long)]
@@ -11,12 +14,67 @@ pub struct Commit {
     #[structopt(short, long)]
     pub scope: Option<String>,
 
+    /// Skips the scope prompt when the 'scope_paths' config maps every file to one scope
+    #[structopt(long)]
+    pub auto_scope: bool,
+
     /// Sets the main message component of the commit (optional; otherwise interactive prompt)
     #[structopt(short, long)]
     pub message: Option<String>,
 
+    /// Marks the commit as a breaking change, adding '!' after the type/scope
     #[structopt(short, long)]
-    pub all: bool,
+    pub breaking: bool,
+
+    /// Describes a breaking change in a 'BREAKING CHANGE' footer (implies --breaking)
+    #[structopt(long, value_name = "description")]
+    pub breaking_change: Option<String>,
+
+    /// Sets the body of the commit, which goes between the message and the footers
+    #[structopt(long)]
+    pub body: Option<String>,
+
+    /// Adds a footer such as 'Refs: #123' or 'Reviewed-by: Name'. May be repeated.
+    #[structopt(long = "footer", value_name = "footer", number_of_values = 1)]
+    pub footers: Vec<Footer>,
+
+    /// Read the message from a file, e.g. one generated from a template. Comment lines are
+    /// ignored. Flags take precedence over its parts.
+    #[structopt(short, long, parse(from_os_str))]
+    pub file: Option<PathBuf>,
+
+    /// Read the message from standard input, like --file. If it's invalid, the prompts
+    /// still open on the terminal, unless there's none or --no-interactive is passed.
+    #[structopt(long)]
+    pub stdin: bool,
+
+    /// Never prompt. Missing or invalid fields are reported as JSON on stderr.
+    /// Implied when stdin isn't a terminal, except with --stdin.
+    #[structopt(long)]
+    pub no_interactive: bool,
+
+    /// Always show the Files prompt, with the staged files checked. Unchecking one
+    /// unstages it before committing.
+    #[structopt(long, conflicts_with = "amend")]
+    pub pick: bool,
+
+    /// Edit the previous commit, starting the prompts with its type, scope and message.
+    /// Staged changes are added to it.
+    #[structopt(long)]
+    pub amend: bool,
+
+    /// With --amend, edit the previous commit even if it has been pushed
+    #[structopt(long, requires = "amend")]
+    pub force: bool,
+
+    /// Run the prompts, then print the message, the files that would be staged, and the
+    /// git commands instead of running them
+    #[structopt(long, alias = "print")]
+    pub dry_run: bool,
+
+    /// With --dry-run, print a JSON object for editor integrations
+    #[structopt(long, requires = "dry-run")]
+    pub json: bool,
 
     /// Arguments which will be passed to 'git commit'.
     /// Pass a '--' argument before the git args to disable special parsing.
@@ -26,22 +84,10 @@ pub struct Commit {
 
 #[derive(StructOpt)]
 pub struct Log {
-    /// Filter by 'type' e.g. 'feat'
-    #[structopt(short, long)]
-    pub ty: Option<String>,
-
-    /// Filter by 'scope' e.g. 'client'
-    #[structopt(short, long)]
-    pub scope: Option<String>,
-
     /// Number of commits to display.
     #[structopt(short, long)]
     pub num: Option<usize>,
 
-    /// Output log info as a JSON array of objects
-    #[structopt(short, long)]
-    pub json: bool,
-
     /// Only useful when filing bug reports for glint.
     #[structopt(short, long)]
     pub debug: bool,
@@ -52,20 +98,143 @@ pub struct Log {
     pub git_args: Vec<String>,
 }
 
+#[derive(StructOpt)]
+pub struct Lint {
+    /// Read the message from a file, e.g. '.git/COMMIT_EDITMSG'. Comment lines are ignored.
+    #[structopt(short, long, parse(from_os_str))]
+    pub file: Option<PathBuf>,
+
+    /// Read the message from standard input
+    #[structopt(long)]
+    pub stdin: bool,
+
+    /// Only report errors, not warnings
+    #[structopt(short, long)]
+    pub quiet: bool,
+
+    /// A revision range whose commits will be checked, e.g. 'origin/main..HEAD'
+    pub range: Option<String>,
+}
+
+#[derive(StructOpt)]
+pub struct Changelog {
+    /// The version to list the changes under, e.g. '1.2.0'. Without it, they're listed as
+    /// 'Unreleased'.
+    #[structopt(short, long = "release", value_name = "version")]
+    pub release: Option<String>,
+
+    /// Either 'keep-a-changelog' or 'conventional'. Defaults to the configured format.
+    #[structopt(long)]
+    pub format: Option<Format>,
+
+    /// Prepend the release to this file (e.g. 'CHANGELOG.md') instead of printing it
+    #[structopt(short, long, parse(from_os_str))]
+    pub output: Option<PathBuf>,
+
+    /// The revision range to include, e.g. 'v1.0.0..HEAD'. Defaults to the commits since
+    /// the last tag.
+    pub range: Option<String>,
+}
+
+#[derive(StructOpt)]
+pub struct Fixup {
+    /// Create a 'squash!' commit, whose message is added to the target's when squashed
+    #[structopt(long, conflicts_with = "amend")]
+    pub squash: bool,
+
+    /// Create an 'amend!' commit, which also rewords the target. The prompts start with
+    /// its type, scope and message.
+    #[structopt(long)]
+    pub amend: bool,
+
+    /// List all recent commits, not only those that touch the staged files
+    #[structopt(short, long)]
+    pub all: bool,
+
+    /// Number of recent commits to choose from
+    #[structopt(short, long, default_value = "100")]
+    pub num: usize,
+
+    /// Squash the new commit into its target with 'git rebase --autosquash'
+    #[structopt(short, long)]
+    pub rebase: bool,
+
+    /// Arguments which will be passed to 'git commit'.
+    pub git_args: Vec<String>,
+}
+
+#[derive(StructOpt)]
+pub struct Bump {
+    /// Bump to a pre-release with this identifier, e.g. 'rc' for '1.3.0-rc.0'
+    #[structopt(long, value_name = "identifier")]
+    pub pre: Option<String>,
+
+    /// Create an annotated tag for the new version
+    #[structopt(short, long)]
+    pub tag: bool,
+
+    /// Update the version in the Cargo.toml and/or package.json in the repository root
+    #[structopt(short, long)]
+    pub write: bool,
+
+    /// Explain which commits decided the bump, without tagging or writing anything
+    #[structopt(long)]
+    pub dry_run: bool,
+}
+
+#[derive(StructOpt)]
+pub enum Hook {
+    /// Install the commit-msg and prepare-commit-msg hooks into this repository
+    Install {
+        /// Replace glint's backup of a previous hook, if there is one
+        #[structopt(short, long)]
+        force: bool,
+    },
+
+    /// Remove glint's hooks, restoring any hooks they replaced
+    Uninstall,
+
+    /// Called by the installed hooks
+    #[structopt(setting = AppSettings::Hidden)]
+    Run {
+        /// The name of the hook being run, e.g. 'commit-msg'
+        hook: String,
+
+        /// The arguments git passed to the hook
+        args: Vec<String>,
+    },
+}
+
 /// A friendly conventional commit tool. You probably want the 'commit' subcommand, or 'c' for short.
 #[derive(StructOpt)]
 pub enum Cli {
     /// Create a new commit
+    #[structopt(alias = "c")]
     Commit(Commit),
 
     /// View recent commits
+    #[structopt(alias = "l")]
     Log(Log),
+
+    /// Check commit messages against the commitlint rules
+    Lint(Lint),
+
+    /// Manage the git hooks that run glint when committing from other tools
+    Hook(Hook),
+
+    /// Create a fixup!, squash! or amend! commit for one of the recent commits
+    Fixup(Fixup),
+
+    /// Generate release notes from the conventional commits in a range
+    Changelog(Changelog),
+
+    /// Recommend the next semantic version based on the commits since the last release
+    Bump(Bump),
 }
 
 pub fn parse() -> Cli {
     let matches = Cli::clap()
         .setting(AppSettings::TrailingVarArg)
-        .setting(AppSettings::InferSubcommands)
         .setting(AppSettings::SubcommandRequiredElseHelp)
         .get_matches();
 
@@ -16,19 +16,17 @@ pub fn theme_user_input() -> Color {
     Color::Blue
 }
 
-// pub fn glint_type_to_color(ty: &str) -> Color {
-//     match ty {
-//         "build" => Color::White,
-//         "ci" => Color::Blue,
-//         "chore" => Color::Yellow,
-//         "docs" => Color::DarkBlue,
-//         "feat" => Color::Blue,
-//         "fix" => Color::Red,
-//         "perf" => Color::Green,
-//         "refactor" => Color::DarkCyan,
-//         "revert" => Color::DarkRed,
-//         "style" => Color::Cyan,
-//         "test" => Color::Magenta,
-//         _ => Color::White,
-//     }
-// }
+/// Deserializes a color name such as `"dark_cyan"`.
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Color>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::de::Error;
+    use serde::Deserialize;
+    use std::convert::TryFrom;
+
+    let name = String::deserialize(deserializer)?;
+    Color::try_from(name.as_str())
+        .map(Some)
+        .map_err(|_| D::Error::custom(format!("unknown color `{}`", name)))
+}
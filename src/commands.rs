@@ -1,5 +1,15 @@
+mod bump;
+mod changelog;
 mod commit;
+mod fixup;
+mod hook;
+mod lint;
 mod log;
 
+pub use bump::bump;
+pub use changelog::changelog;
 pub use commit::commit;
+pub use fixup::fixup;
+pub use hook::hook;
+pub use lint::lint;
 pub use log::log;
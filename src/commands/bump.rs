@@ -0,0 +1,178 @@
+use crate::cli;
+use glint::version::{self, Bump, Version};
+use glint::{Config, Git, LogItem};
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// The manifests whose version `--write` updates, relative to the repo root.
+const MANIFESTS: &[&str] = &["Cargo.toml", "package.json"];
+
+pub fn bump(params: cli::Bump, _config: Config) {
+    let git = match Git::from_cwd() {
+        Ok(git) => git,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+
+    if let Err(err) = run(params, &git) {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+}
+
+/// The version in the first manifest that has one, for repos that haven't been tagged yet.
+fn manifest_version(git: &Git) -> Option<Version> {
+    MANIFESTS.iter().find_map(|name| {
+        let contents = fs::read_to_string(git.repo_root().join(name)).ok()?;
+        let version = if *name == "package.json" {
+            let json: serde_json::Value = serde_json::from_str(&contents).ok()?;
+            json.get("version")?.as_str()?.to_string()
+        } else {
+            let toml: toml::Value = toml::from_str(&contents).ok()?;
+            toml.get("package")?.get("version")?.as_str()?.to_string()
+        };
+        version.parse().ok()
+    })
+}
+
+/// Writes `next` into each manifest that has a version. Returns the updated paths.
+fn write_manifests(git: &Git, next: &Version) -> io::Result<Vec<PathBuf>> {
+    let mut written = vec![];
+
+    for name in MANIFESTS {
+        let path = git.repo_root().join(name);
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err),
+        };
+
+        let updated = if *name == "package.json" {
+            version::set_package_json_version(&contents, next)
+        } else {
+            version::set_cargo_version(&contents, next)
+        };
+
+        if let Some(updated) = updated {
+            fs::write(&path, updated)?;
+            written.push(path);
+        }
+    }
+
+    Ok(written)
+}
+
+fn run(params: cli::Bump, git: &Git) -> io::Result<()> {
+    let last_tag = git.last_version_tag()?;
+    let (current, range) = match last_tag {
+        Some((ref tag, ref version)) => (version.clone(), format!("{}..HEAD", tag)),
+        None => (
+            manifest_version(git).unwrap_or_else(|| Version::new(0, 0, 0)),
+            "HEAD".to_string(),
+        ),
+    };
+
+    let logs = git.log_parsed([&range])?;
+    let bumps: Vec<(&LogItem, Option<Bump>)> = logs
+        .iter()
+        .map(|log| {
+            (
+                log,
+                log.as_conventional().ok().map(|c| Bump::for_commit(&c)),
+            )
+        })
+        .collect();
+
+    let bump = match bumps.iter().filter_map(|(_, bump)| *bump).max() {
+        Some(bump) => bump,
+        None => {
+            return Err(io::Error::other(format!(
+                "There are no conventional commits in {}, so there's nothing to release.",
+                range
+            )));
+        }
+    };
+
+    let next = current.bump(bump, params.pre.as_deref());
+    // Follow the existing tags' style, which is usually a 'v' prefix.
+    let prefix = match last_tag {
+        Some((ref tag, _)) if !tag.starts_with('v') => "",
+        _ => "v",
+    };
+    let tag_name = format!("{}{}", prefix, next);
+
+    if params.dry_run {
+        match last_tag {
+            Some((ref tag, _)) => println!("Current version: {} (tag {})", current, tag),
+            None => println!("Current version: {} (no version tags yet)", current),
+        }
+
+        println!("\nCommits in {}:", range);
+        for (log, bump) in bumps.iter() {
+            let header = log.message.lines().next().unwrap_or("");
+            let bump = bump.map_or("ignored".to_string(), |bump| bump.to_string());
+            println!("  {:<7} {} {}", bump, &log.commit[..8], header);
+        }
+
+        let effective = current.effective_bump(bump);
+        if effective != bump {
+            println!("\nBump: {} ({} before 1.0.0)", effective, bump);
+        } else {
+            println!("\nBump: {}", bump);
+        }
+        println!("Next version: {}", next);
+
+        if params.write {
+            for name in MANIFESTS {
+                if git.repo_root().join(name).is_file() {
+                    println!("Would update the version in {}", name);
+                }
+            }
+        }
+        if params.tag {
+            println!("Would create the tag {}", tag_name);
+        }
+
+        return Ok(());
+    }
+
+    println!("{}", next);
+
+    if params.write {
+        let written = write_manifests(git, &next)?;
+        for path in written.iter() {
+            eprintln!("Updated {}", path.display());
+        }
+
+        // Tag the release commit, rather than the commit before the version changed. Only
+        // the manifests go into it, leaving anything else that's staged for later.
+        if params.tag && !written.is_empty() {
+            let message = format!("chore(release): {}", next);
+            let only = ["--only", "--"]
+                .iter()
+                .map(OsStr::new)
+                .chain(written.iter().map(|path| path.as_os_str()));
+            if !git.add(&written).status()?.success()
+                || !git.commit(&message, only).status()?.success()
+            {
+                return Err(io::Error::other("Failed to commit the updated versions."));
+            }
+        }
+    }
+
+    if params.tag {
+        if !git.tag(&tag_name, &tag_name).status()?.success() {
+            return Err(io::Error::other(format!(
+                "Failed to create the tag {}.",
+                tag_name
+            )));
+        }
+        eprintln!("Created the tag {}", tag_name);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use crate::cli;
+use glint::changelog::{self, Release};
+use glint::{Config, Git};
+use std::fs;
+use std::io;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub fn changelog(params: cli::Changelog, config: Config) {
+    let git = match Git::from_cwd() {
+        Ok(git) => git,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+
+    if let Err(err) = run(params, config, &git) {
+        eprintln!("Failed to generate the changelog: {}", err);
+        std::process::exit(1);
+    }
+}
+
+fn run(params: cli::Changelog, config: Config, git: &Git) -> io::Result<()> {
+    let range = match params.range {
+        Some(range) => range,
+        None => match git.last_version_tag()? {
+            Some((tag, _)) => format!("{}..HEAD", tag),
+            None => "HEAD".to_string(),
+        },
+    };
+    let logs = git.log_parsed([&range])?;
+
+    let mut options = config.changelog.clone();
+    if let Some(format) = params.format {
+        options.format = format;
+    }
+    options.sections = Some(config.changelog_sections(options.format));
+    if options.commit_url.is_none() {
+        options.commit_url = git
+            .remote_url("origin")?
+            .and_then(|remote| changelog::commit_url_for_remote(&remote));
+    }
+
+    // Unreleased changes aren't dated.
+    let date = params.release.as_ref().map(|_| {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |now| now.as_secs() as i64);
+        changelog::date_from_epoch(now)
+    });
+    let release = Release {
+        version: params.release,
+        date,
+    };
+    let rendered = changelog::render(&logs, &release, &options);
+
+    match params.output {
+        Some(path) => {
+            let existing = match fs::read_to_string(&path) {
+                Ok(existing) => existing,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+                Err(err) => return Err(err),
+            };
+            fs::write(
+                &path,
+                changelog::prepend(&existing, &rendered, options.format),
+            )?;
+            println!(
+                "Updated {} with {} commits from {}",
+                path.display(),
+                logs.len(),
+                range
+            );
+        }
+        None => print!("{}", rendered),
+    }
+
+    Ok(())
+}
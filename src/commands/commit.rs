@@ -1,9 +1,13 @@
 use crate::cli;
 use crossterm::{self as ct, cursor, terminal};
-use glint::{prompt, Commit, Config, Git};
-use std::io::Write as _Write;
+use glint::commitlint::{self, Footer, ParseError, ParseErrorKind};
+use glint::prompt::{self, ChosenFiles};
+use glint::{Commit, Config, Git, GitStatus};
+use std::fs;
+use std::io::{self, IsTerminal, Read, Write as _Write};
+use std::process::Command;
 
-fn with_raw<R>(f: impl FnOnce() -> R) -> R {
+pub fn with_raw<R>(f: impl FnOnce() -> R) -> R {
     let result = match terminal::enable_raw_mode() {
         Err(_) => {
             eprintln!("Failed to convert stdio to raw mode. Can't continue.");
@@ -17,11 +21,44 @@ fn with_raw<R>(f: impl FnOnce() -> R) -> R {
     result
 }
 
-fn exit<R>(code: i32) -> Option<R> {
+pub fn exit<R>(code: i32) -> Option<R> {
     let _r = terminal::disable_raw_mode();
     std::process::exit(code)
 }
 
+pub enum Stage {
+    Files,
+    Type,
+    Scope(String),
+    Message(String, Option<String>),
+    Breaking(Commit),
+    Body(Commit),
+    Footers(Commit),
+    Complete(Commit),
+}
+
+/// Fields that are missing or invalid when glint can't prompt for them.
+#[derive(Default)]
+struct Problems {
+    missing: Vec<&'static str>,
+    invalid: Vec<(&'static str, String)>,
+}
+
+/// A message passed with `--file` or `--stdin`, split into its parts.
+struct Draft {
+    commit: Commit,
+    /// Why the message isn't a conventional commit. Its first line is then taken as the
+    /// message, and the rest as the body.
+    error: Option<ParseError>,
+}
+
+/// What was collected by `run_prompts`.
+pub struct Answers {
+    pub commit: Commit,
+    /// The files chosen in the Files prompt, if it was shown.
+    pub files: Option<ChosenFiles>,
+}
+
 pub fn commit(params: cli::Commit, config: Config) {
     let git = match Git::from_cwd() {
         Ok(git) => git,
@@ -31,57 +68,594 @@ pub fn commit(params: cli::Commit, config: Config) {
         }
     };
 
-    enum Stage {
-        Files,
-        Type,
-        Scope(String),
-        Message(String, Option<String>),
-        Complete(String, Option<String>, String),
+    let git_status = git.status().ok();
+    for path in git_status.iter().flat_map(GitStatus::skipped) {
+        eprintln!("Skipping {}: its path isn't valid UTF-8.", path);
     }
+    let message = read_message(&params);
+    let interactive = !params.no_interactive && has_terminal(&params);
+    let from_message = message.is_some();
+    let message = match message {
+        None if params.amend => Some(head_message(&params, &git)),
+        message => message,
+    };
+    let draft = message.map(|message| Draft::parse(&message));
 
-    let mut stage = Stage::Type;
+    // Without prompts, or with a whole message to start from, commit straight away if
+    // everything is valid. When amending, the prompts start with HEAD's message instead.
+    let direct = if from_message || !interactive {
+        match check_commit(&params, draft.as_ref(), &config, git_status.as_ref()) {
+            Ok(commit) => Some(Answers {
+                commit,
+                files: None,
+            }),
+            Err(problems) if !interactive => report(problems),
+            Err(_) => None,
+        }
+    } else {
+        None
+    };
 
-    let git_status = git.status().ok();
+    let answers = if let Some(answers) = direct {
+        answers
+    } else {
+        let mut stage = Stage::Type;
 
-    if let Some(ref git_status) = git_status {
-        let any_staged = git_status.any_staged();
-        let any_unstaged = git_status.any_unstaged();
+        if let Some(ref git_status) = git_status {
+            let any_staged = git_status.any_staged();
+            let any_unstaged = git_status.any_unstaged();
 
-        if !any_staged && any_unstaged {
-            if params.git_args.is_empty() {
+            if params.amend {
+                // Rewording HEAD doesn't need any changes.
+            } else if params.pick && (any_staged || any_unstaged) {
                 stage = Stage::Files;
+            } else if !any_staged && any_unstaged {
+                if params.git_args.is_empty() {
+                    stage = Stage::Files;
+                }
+            } else if !any_staged {
+                eprintln!("No changes to commit.");
+                std::process::exit(1);
             }
-        } else if !any_staged {
-            eprintln!("No changes to commit.");
+        }
+
+        let draft = draft.map(|draft| draft.commit);
+        match run_prompts(&params, &config, &git, git_status, stage, draft) {
+            Some(answers) => answers,
+            None => std::process::exit(0),
+        }
+    };
+
+    let git_message = answers.commit.build_message();
+    let chosen = answers.files.unwrap_or_default();
+    let mut restore = Some(&chosen.unstage)
+        .filter(|files| !files.is_empty())
+        .map(|files| git.restore_staged(files));
+    let mut add = Some(&chosen.add)
+        .filter(|files| !files.is_empty())
+        .map(|files| git.add(files));
+    let mut apply = chosen.patch.as_ref().map(|_| git.apply_cached());
+    let amend = Some("--amend").filter(|_| params.amend);
+    let git_args = amend
+        .into_iter()
+        .chain(params.git_args.iter().map(String::as_str));
+    let mut commit = git.commit(&git_message, git_args);
+
+    if params.dry_run {
+        print_dry_run(
+            &git_message,
+            &chosen,
+            restore
+                .iter()
+                .chain(add.iter())
+                .chain(apply.iter())
+                .chain(Some(&commit)),
+            params.json,
+        );
+        return;
+    }
+
+    if let Some(ref mut restore) = restore {
+        match restore.status() {
+            Ok(status) if status.success() => {}
+            _ => {
+                eprintln!("Failed to unstage {}", chosen.unstage.join(", "));
+                std::process::exit(1);
+            }
+        }
+    }
+
+    if let Some(ref mut add) = add {
+        let _r = add.status();
+    }
+
+    if let (Some(apply), Some(patch)) = (apply.as_mut(), chosen.patch.as_ref()) {
+        if let Err(err) = apply_patch(apply, &patch.text) {
+            eprintln!("Failed to stage the chosen changes: {}", err);
+            std::process::exit(1);
+        }
+    }
+
+    match commit.status() {
+        Ok(status) if status.success() => println!("Commit successful."),
+        Ok(status) => match status.code() {
+            Some(code) => {
+                eprintln!("Commit command failed with {}", code);
+                std::process::exit(code);
+            }
+            None => {
+                eprintln!(
+                    "Commit command failed with no status. Was likely killed by another process."
+                );
+                std::process::exit(1);
+            }
+        },
+        Err(err) => {
+            eprintln!(
+                "Failed to run git. This is the best error I have:\n{:?}",
+                err
+            );
+            std::process::exit(1);
+        }
+    };
+}
+
+/// The message of HEAD, for `--amend`. Exits if there's no HEAD, or if it has been pushed
+/// and `--force` wasn't passed.
+fn head_message(params: &cli::Commit, git: &Git) -> String {
+    let head = match git.log_parsed(["-1", "HEAD"]) {
+        Ok(mut logs) if !logs.is_empty() => logs.remove(0),
+        Ok(_) | Err(_) => {
+            eprintln!("There's no commit to amend.");
+            std::process::exit(1);
+        }
+    };
+
+    if !params.force {
+        match git.remote_branches_containing(&head.commit) {
+            Ok(branches) if branches.is_empty() => {}
+            Ok(branches) => {
+                eprintln!(
+                    "HEAD has already been pushed to {}. Pass --force to amend it anyway.",
+                    branches.join(", ")
+                );
+                std::process::exit(1);
+            }
+            Err(err) => {
+                eprintln!("Failed to check whether HEAD has been pushed: {}", err);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    head.message
+}
+
+/// Whether the prompts can be shown. With `--stdin`, the message has been read by now, so
+/// stdin is replaced with the terminal, which is where crossterm reads keys and sets raw
+/// mode.
+fn has_terminal(params: &cli::Commit) -> bool {
+    if io::stdin().is_terminal() {
+        return true;
+    }
+
+    params.stdin && io::stdout().is_terminal() && stdin_from_tty()
+}
+
+#[cfg(unix)]
+fn stdin_from_tty() -> bool {
+    use std::os::unix::io::AsRawFd;
+
+    let tty = match fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+    {
+        Ok(tty) => tty,
+        Err(_) => return false,
+    };
+
+    // Both descriptors are open, and stdin keeps the terminal after `tty` is closed.
+    unsafe { libc::dup2(tty.as_raw_fd(), libc::STDIN_FILENO) != -1 }
+}
+
+#[cfg(not(unix))]
+fn stdin_from_tty() -> bool {
+    false
+}
+
+/// The message passed with `--file` or `--stdin`, without git's comment lines.
+fn read_message(params: &cli::Commit) -> Option<String> {
+    let message = if let Some(ref path) = params.file {
+        fs::read_to_string(path)
+    } else if params.stdin {
+        let mut message = String::new();
+        io::stdin().read_to_string(&mut message).map(|_| message)
+    } else {
+        return None;
+    };
+
+    match message {
+        Ok(message) => Some(commitlint::strip_comments(&message)),
+        Err(err) => {
+            eprintln!("Failed to read the commit message: {}", err);
             std::process::exit(1);
         }
     }
+}
+
+impl Draft {
+    fn parse(message: &str) -> Self {
+        match commitlint::parse(message) {
+            Ok(parsed) => Draft {
+                commit: parsed.to_commit(),
+                error: None,
+            },
+            Err(err) => {
+                let (header, body) = message.split_once('\n').unwrap_or((message, ""));
+                let commit = Commit {
+                    message: header.trim().to_string(),
+                    body: Some(body.trim().to_string()).filter(|body| !body.is_empty()),
+                    ..Default::default()
+                };
+                Draft {
+                    commit,
+                    error: Some(err),
+                }
+            }
+        }
+    }
+
+    /// The flags, with whatever they don't set taken from the draft.
+    fn fill(&self, params: &cli::Commit) -> cli::Commit {
+        let draft = &self.commit;
+        cli::Commit {
+            ty: params
+                .ty
+                .clone()
+                .or_else(|| Some(draft.ty.clone()).filter(|ty| !ty.is_empty())),
+            scope: params.scope.clone().or_else(|| draft.scope.clone()),
+            message: params
+                .message
+                .clone()
+                .or_else(|| Some(draft.message.clone()).filter(|message| !message.is_empty())),
+            breaking: params.breaking || draft.breaking,
+            body: params.body.clone().or_else(|| draft.body.clone()),
+            footers: draft
+                .footers
+                .iter()
+                .chain(&params.footers)
+                .cloned()
+                .collect(),
+            ..params.clone()
+        }
+    }
+}
+
+/// Builds the commit from the flags and draft alone, checking them like the prompts would.
+fn check_commit(
+    params: &cli::Commit,
+    draft: Option<&Draft>,
+    config: &Config,
+    git_status: Option<&GitStatus>,
+) -> Result<Commit, Problems> {
+    let params = match draft {
+        Some(draft) => draft.fill(params),
+        None => params.clone(),
+    };
+    let mut problems = Problems::default();
+
+    if let Some(err) = draft.and_then(|draft| draft.error.as_ref()) {
+        let message = format!("The message isn't a conventional commit: {}", err.kind);
+        problems.invalid.push(("message", message));
+    }
+
+    // Extra git args such as `-a` may stage files themselves, and amending can reword HEAD
+    // without any.
+    let no_files = git_status.is_some_and(|status| !status.any_staged());
+    if no_files && params.git_args.is_empty() && !params.amend {
+        problems.missing.push("files");
+    }
+
+    match params.ty {
+        Some(ref ty) => {
+            if let Err(err) = config.check_type(ty) {
+                problems.invalid.push(("type", err));
+            }
+        }
+        None => problems.missing.push("type"),
+    }
+
+    if let Some(ref scope) = params.scope {
+        if let Err(err) = config.check_scope(scope) {
+            problems.invalid.push(("scope", err));
+        }
+    }
+
+    let message = params.message.as_deref().unwrap_or("").trim();
+    if message.is_empty() {
+        problems.missing.push("message");
+    }
+
+    let ty = params.ty.clone().unwrap_or_default();
+    let mut commit = Commit {
+        emoji: config
+            .gitmoji_for(&ty)
+            .or_else(|| draft.and_then(|draft| draft.commit.emoji.clone())),
+        ty,
+        scope: params.scope.clone().filter(|scope| !scope.is_empty()),
+        breaking: params.breaking,
+        message: message.to_string(),
+        body: params.body.clone(),
+        footers: params.footers.clone(),
+    };
+    if let Some(ref description) = params.breaking_change {
+        commit.add_breaking_change(description);
+    }
+
+    if !problems.missing.is_empty() || !problems.invalid.is_empty() {
+        return Err(problems);
+    }
+
+    // Anything else that would make a malformed header, e.g. a ')' in the scope.
+    if let Err(err) = commitlint::parse(&commit.build_message()) {
+        let field = match err.kind {
+            ParseErrorKind::MissingType | ParseErrorKind::InvalidTypeChar(_) => "type",
+            ParseErrorKind::UnclosedScope | ParseErrorKind::EmptyScope => "scope",
+            _ => "message",
+        };
+        let message = format!("The header is invalid: {}", err.kind);
+        problems.invalid.push((field, message));
+        return Err(problems);
+    }
+
+    Ok(commit)
+}
+
+/// Prints the problems as JSON on stderr and exits, e.g.
+/// `{"error":"...","missing":["type"],"invalid":[{"field":"scope","message":"..."}]}`.
+fn report(problems: Problems) -> ! {
+    let summary: Vec<String> = problems
+        .missing
+        .iter()
+        .map(|field| format!("missing {}", field))
+        .chain(problems.invalid.iter().map(|(_, message)| message.clone()))
+        .collect();
+    let report = serde_json::json!({
+        "error": format!("Can't commit without prompting: {}", summary.join("; ")),
+        "missing": problems.missing,
+        "invalid": problems
+            .invalid
+            .iter()
+            .map(|(field, message)| serde_json::json!({ "field": field, "message": message }))
+            .collect::<Vec<_>>(),
+    });
+    eprintln!("{}", report);
+    std::process::exit(1);
+}
 
-    let mut commit_files: Option<Vec<String>> = None;
+/// Prints what `glint commit` would do: the message, the files it would stage, and the
+/// commands it would run, either for reading or as a JSON object.
+fn print_dry_run<'a>(
+    message: &str,
+    chosen: &ChosenFiles,
+    commands: impl Iterator<Item = &'a Command>,
+    json: bool,
+) {
+    let commands: Vec<Vec<String>> = commands.map(argv).collect();
+    let (files, patch) = (&chosen.add, chosen.patch.as_ref());
+
+    if json {
+        let output = serde_json::json!({
+            "message": message,
+            "files": files,
+            "partial_files": patch.map_or(&[][..], |patch| &patch.files),
+            "patch": patch.map(|patch| &patch.text),
+            "unstage": chosen.unstage,
+            "commands": commands,
+        });
+        println!("{}", output);
+        return;
+    }
+
+    println!("Message:\n");
+    for line in message.lines() {
+        println!("    {}", line);
+    }
+
+    println!();
+    if files.is_empty() && patch.is_none() {
+        println!("Files to stage: none, the staged files are committed");
+    } else {
+        println!("Files to stage:\n");
+        for file in files {
+            println!("    {}", file);
+        }
+        for file in patch.iter().flat_map(|patch| &patch.files) {
+            println!("    {} (partly)", file);
+        }
+    }
+
+    if !chosen.unstage.is_empty() {
+        println!("\nFiles to unstage:\n");
+        for file in &chosen.unstage {
+            println!("    {}", file);
+        }
+    }
+
+    if let Some(patch) = patch {
+        println!("\nPatch:\n");
+        for line in patch.text.lines() {
+            println!("    {}", line);
+        }
+    }
+
+    println!("\nCommands:\n");
+    for command in commands {
+        let quoted: Vec<String> = command.iter().map(|arg| shell_quote(arg)).collect();
+        println!("    {}", quoted.join(" "));
+    }
+}
+
+/// Runs `git apply --cached`, writing the patch to its stdin.
+fn apply_patch(apply: &mut Command, patch: &str) -> io::Result<()> {
+    let mut child = apply.spawn()?;
+    child
+        .stdin
+        .take()
+        .expect("git apply's stdin is piped")
+        .write_all(patch.as_bytes())?;
+
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(io::Error::other(format!(
+            "git apply failed with {}",
+            status
+        )));
+    }
+
+    Ok(())
+}
+
+/// The program and arguments of a command.
+fn argv(command: &Command) -> Vec<String> {
+    std::iter::once(command.get_program())
+        .chain(command.get_args())
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect()
+}
+
+/// Quotes `arg` for a POSIX shell, if it needs it.
+fn shell_quote(arg: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "-_./:=@,+%".contains(c);
+    if !arg.is_empty() && arg.chars().all(plain) {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+/// The number of commits whose scopes are suggested.
+const SCOPE_HISTORY: usize = 200;
+
+/// The files being committed: those chosen in the Files prompt, or else the staged files.
+fn files_to_commit(
+    git: &Git,
+    git_status: &Option<GitStatus>,
+    commit_files: &Option<ChosenFiles>,
+) -> Vec<String> {
+    match (commit_files, git_status) {
+        (Some(chosen), _) => chosen.files.clone(),
+        (None, Some(status)) => status.staged_files(),
+        (None, None) => git
+            .status()
+            .map(|status| status.staged_files())
+            .unwrap_or_default(),
+    }
+}
+
+/// Ranks the scopes from recent history and the files being committed.
+fn suggest_scopes(config: &Config, git: &Git, files: &[String]) -> Vec<String> {
+    let logs = git
+        .log_parsed([format!("-{}", SCOPE_HISTORY)])
+        .unwrap_or_default();
+
+    glint::scopes::suggest(&logs, files, &config.scope_paths, &config.scopes)
+}
+
+/// Walks through the prompts starting at `stage`, skipping any whose value was passed as
+/// a flag. The others start with the parts of `draft`, if given. Escaping the Type prompt
+/// goes back to the Files prompt, or returns None when there's no `git_status` to show one.
+pub fn run_prompts(
+    params: &cli::Commit,
+    config: &Config,
+    git: &Git,
+    git_status: Option<GitStatus>,
+    mut stage: Stage,
+    draft: Option<Commit>,
+) -> Option<Answers> {
+    let draft = draft.unwrap_or_default();
+
+    let mut commit_files: Option<ChosenFiles> = None;
 
     let mut escape_clear_lines = 0;
 
+    // The optional stages after the Message prompt. Passing the message on the command
+    // line implies the commit is complete, and flags answer the stages they cover.
+    let quick = params.message.is_some();
+    let ask_breaking = config.stages.breaking
+        && !quick
+        && !params.breaking
+        && params.breaking_change.is_none()
+        && !params.footers.iter().any(Footer::is_breaking_change)
+        && !draft.breaking;
+    let ask_body = config.stages.body && !quick && params.body.is_none();
+    let ask_footers = config.stages.footers && !quick;
+
+    let after_body = |commit| {
+        if ask_footers {
+            Stage::Footers(commit)
+        } else {
+            Stage::Complete(commit)
+        }
+    };
+    let after_breaking = |commit| {
+        if ask_body {
+            Stage::Body(commit)
+        } else {
+            after_body(commit)
+        }
+    };
+    let after_message = |commit| {
+        if ask_breaking {
+            Stage::Breaking(commit)
+        } else {
+            after_breaking(commit)
+        }
+    };
+    // Escape steps back through the same stages.
+    let before_body = |commit: Commit| {
+        if ask_breaking {
+            Stage::Breaking(commit)
+        } else {
+            Stage::Message(commit.ty, commit.scope)
+        }
+    };
+    let before_footers = |commit| {
+        if ask_body {
+            Stage::Body(commit)
+        } else {
+            before_body(commit)
+        }
+    };
+
     loop {
         match stage {
             Stage::Files => {
-                commit_files = with_raw(|| {
-                    match prompt::FilesPrompt::new(&config, &git, git_status.clone().unwrap()).run()
-                    {
-                        prompt::FilesPromptResult::Files(files) => Some(files),
-                        prompt::FilesPromptResult::Terminate => exit(2),
-                        prompt::FilesPromptResult::Escape => exit(0),
-                    }
-                });
+                let git_status = git_status.clone()?;
+                let chosen =
+                    with_raw(
+                        || match prompt::FilesPrompt::new(config, git, git_status).run() {
+                            prompt::FilesPromptResult::Files(chosen) => Some(chosen),
+                            prompt::FilesPromptResult::Terminate => exit(2),
+                            prompt::FilesPromptResult::Escape => exit(0),
+                        },
+                    );
+                if let Some(chosen) = chosen {
+                    commit_files = Some(chosen);
+                }
 
                 stage = Stage::Type;
             }
             Stage::Type => {
                 let ty = match params.ty {
                     Some(ref ty) => Some(ty.to_string()),
-                    None => with_raw(|| match prompt::TypePrompt::new(&config).run() {
-                        prompt::TypePromptResult::Type(ty) => Some(ty),
-                        prompt::TypePromptResult::Terminate => exit(2),
-                        prompt::TypePromptResult::Escape => None,
+                    None => with_raw(|| {
+                        match prompt::TypePrompt::new(config, Some(draft.ty.as_str())).run() {
+                            prompt::TypePromptResult::Type(ty) => Some(ty),
+                            prompt::TypePromptResult::Terminate => exit(2),
+                            prompt::TypePromptResult::Escape => None,
+                        }
                     }),
                 };
 
@@ -98,11 +672,34 @@ pub fn commit(params: cli::Commit, config: Config) {
             Stage::Scope(ty) => {
                 let scope = match params.scope {
                     Some(ref scope) => Some((Some(scope.to_string()), 0)),
-                    None => with_raw(|| match prompt::ScopePrompt::new(&config, &ty).run() {
-                        prompt::ScopePromptResult::Scope(scope, lines) => Some((scope, lines)),
-                        prompt::ScopePromptResult::Terminate => exit(2),
-                        prompt::ScopePromptResult::Escape => None,
-                    }),
+                    None => {
+                        let files = files_to_commit(git, &git_status, &commit_files);
+                        let mapped = glint::scopes::mapped_scopes(&config.scope_paths, &files)
+                            .map(|scopes| scopes.join(","));
+
+                        match mapped {
+                            Some(scope)
+                                if draft.scope.is_none()
+                                    && params.auto_scope
+                                    && !scope.contains(',') =>
+                            {
+                                Some((Some(scope), 0))
+                            }
+                            mapped => {
+                                let initial = draft.scope.clone().or(mapped);
+                                let suggestions = suggest_scopes(config, git, &files);
+                                let prompt =
+                                    prompt::ScopePrompt::new(config, &ty, suggestions, initial);
+                                with_raw(|| match prompt.run() {
+                                    prompt::ScopePromptResult::Scope(scope, lines) => {
+                                        Some((scope, lines))
+                                    }
+                                    prompt::ScopePromptResult::Terminate => exit(2),
+                                    prompt::ScopePromptResult::Escape => None,
+                                })
+                            }
+                        }
+                    }
                 };
 
                 let (scope, lines) = match scope {
@@ -119,10 +716,12 @@ pub fn commit(params: cli::Commit, config: Config) {
             Stage::Message(ty, scope) => {
                 let message = match params.message {
                     Some(ref message) => Some(message.to_string()),
-                    None => with_raw(|| match prompt::MessagePrompt::new(&config).run() {
-                        prompt::MessagePromptResult::Message(message) => Some(message),
-                        prompt::MessagePromptResult::Terminate => exit(2),
-                        prompt::MessagePromptResult::Escape => None,
+                    None => with_raw(|| {
+                        match prompt::MessagePrompt::new(Some(draft.message.as_str())).run() {
+                            prompt::MessagePromptResult::Message(message) => Some(message),
+                            prompt::MessagePromptResult::Terminate => exit(2),
+                            prompt::MessagePromptResult::Escape => None,
+                        }
                     }),
                 };
 
@@ -143,40 +742,200 @@ pub fn commit(params: cli::Commit, config: Config) {
                     }
                 };
 
-                stage = Stage::Complete(ty, scope, message);
-            }
-            Stage::Complete(ty, scope, message) => {
-                if let Some(commit_files) = commit_files {
-                    let _r = git.add(commit_files).status();
+                let mut commit = Commit {
+                    emoji: config.gitmoji_for(&ty).or_else(|| draft.emoji.clone()),
+                    ty,
+                    scope,
+                    breaking: params.breaking || draft.breaking,
+                    message,
+                    body: params.body.clone().or_else(|| draft.body.clone()),
+                    footers: draft
+                        .footers
+                        .iter()
+                        .chain(&params.footers)
+                        .cloned()
+                        .collect(),
+                };
+
+                if let Some(ref description) = params.breaking_change {
+                    commit.add_breaking_change(description);
                 }
 
-                let commit = Commit { ty, scope, message };
+                stage = after_message(commit);
+            }
+            Stage::Breaking(mut commit) => {
+                // Forget the previous answer when stepping back to this stage.
+                commit.breaking = false;
+                commit.footers.retain(|footer| !footer.is_breaking_change());
 
-                let git_message = commit.build_message();
+                let breaking = with_raw(|| match prompt::BreakingPrompt::new().run() {
+                    prompt::BreakingPromptResult::Breaking(breaking, description) => {
+                        Some((breaking, description))
+                    }
+                    prompt::BreakingPromptResult::Terminate => exit(2),
+                    prompt::BreakingPromptResult::Escape => None,
+                });
 
-                match git.commit(&git_message, params.git_args).status() {
-                    Ok(status) if status.success() => println!("Commit successful."),
-                    Ok(status) => match status.code() {
-                        Some(code) => {
-                            eprintln!("Commit command failed with {}", code);
-                            std::process::exit(code);
-                        }
-                        None => {
-                            eprintln!("Commit command failed with no status. Was likely killed by another process.");
-                            std::process::exit(1);
-                        }
-                    },
-                    Err(err) => {
-                        eprintln!(
-                            "Failed to run git. This is the best error I have:\n{:?}",
-                            err
-                        );
-                        std::process::exit(1);
+                stage = match breaking {
+                    Some((_, Some(description))) => {
+                        commit.add_breaking_change(&description);
+                        after_breaking(commit)
+                    }
+                    Some((breaking, None)) => {
+                        commit.breaking = breaking;
+                        after_breaking(commit)
+                    }
+                    None => Stage::Message(commit.ty, commit.scope),
+                };
+            }
+            Stage::Body(mut commit) => {
+                let body =
+                    with_raw(
+                        || match prompt::MessagePrompt::body(commit.body.as_deref()).run() {
+                            prompt::MessagePromptResult::Message(body) => Some(body),
+                            prompt::MessagePromptResult::Terminate => exit(2),
+                            prompt::MessagePromptResult::Escape => None,
+                        },
+                    );
+
+                stage = match body {
+                    Some(body) => {
+                        commit.body = Some(body).filter(|body| !body.trim().is_empty());
+                        after_body(commit)
                     }
+                    None => before_body(commit),
                 };
+            }
+            Stage::Footers(mut commit) => {
+                let footers =
+                    with_raw(
+                        || match prompt::FootersPrompt::new(commit.footers.clone()).run() {
+                            prompt::FootersPromptResult::Footers(footers) => Some(footers),
+                            prompt::FootersPromptResult::Terminate => exit(2),
+                            prompt::FootersPromptResult::Escape => None,
+                        },
+                    );
 
-                return;
+                stage = match footers {
+                    Some(footers) => {
+                        commit.footers = footers;
+                        Stage::Complete(commit)
+                    }
+                    None => before_footers(commit),
+                };
+            }
+            Stage::Complete(commit) => {
+                return Some(Answers {
+                    commit,
+                    files: commit_files,
+                });
             }
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{argv, shell_quote, Draft};
+    use crate::cli;
+    use glint::commitlint::Footer;
+    use std::process::Command;
+
+    fn footer(footer: &str) -> Footer {
+        footer.parse().unwrap()
+    }
+
+    #[test]
+    fn draft_parses_conventional_message() {
+        let draft = Draft::parse("feat(cli)!: add --stdin\n\nReads the message.\n\nRefs: #12");
+
+        assert!(draft.error.is_none());
+        assert_eq!(draft.commit.ty, "feat");
+        assert_eq!(draft.commit.scope.as_deref(), Some("cli"));
+        assert!(draft.commit.breaking);
+        assert_eq!(draft.commit.message, "add --stdin");
+        assert_eq!(draft.commit.body.as_deref(), Some("Reads the message."));
+        assert_eq!(draft.commit.footers, vec![footer("Refs: #12")]);
+    }
+
+    #[test]
+    fn draft_falls_back_for_other_messages() {
+        let draft = Draft::parse("Add --stdin \n\n  Reads the message.\n");
+
+        assert!(draft.error.is_some());
+        assert_eq!(draft.commit.ty, "");
+        assert_eq!(draft.commit.message, "Add --stdin");
+        assert_eq!(draft.commit.body.as_deref(), Some("Reads the message."));
+
+        let draft = Draft::parse("Add --stdin");
+        assert!(draft.error.is_some());
+        assert_eq!(draft.commit.message, "Add --stdin");
+        assert_eq!(draft.commit.body, None);
+    }
+
+    #[test]
+    fn draft_fill_prefers_flags() {
+        let draft = Draft::parse("feat(cli): add --stdin\n\nReads the message.\n\nRefs: #12");
+        let params = cli::Commit {
+            ty: Some("fix".into()),
+            message: Some("read --stdin".into()),
+            footers: vec![footer("Closes #3")],
+            ..Default::default()
+        };
+        let filled = draft.fill(&params);
+
+        assert_eq!(filled.ty.as_deref(), Some("fix"));
+        assert_eq!(filled.scope.as_deref(), Some("cli"));
+        assert_eq!(filled.message.as_deref(), Some("read --stdin"));
+        assert_eq!(filled.body.as_deref(), Some("Reads the message."));
+        assert!(!filled.breaking);
+        assert_eq!(
+            filled.footers,
+            vec![footer("Refs: #12"), footer("Closes #3")]
+        );
+    }
+
+    #[test]
+    fn draft_fill_leaves_missing_parts_unset() {
+        let draft = Draft::parse("Add --stdin");
+        let params = cli::Commit {
+            breaking: true,
+            ..Default::default()
+        };
+        let filled = draft.fill(&params);
+
+        assert_eq!(filled.ty, None);
+        assert_eq!(filled.scope, None);
+        assert_eq!(filled.message.as_deref(), Some("Add --stdin"));
+        assert!(filled.breaking);
+        assert!(filled.footers.is_empty());
+    }
+
+    #[test]
+    fn shell_quote_leaves_plain_args() {
+        assert_eq!(shell_quote("commit"), "commit");
+        assert_eq!(shell_quote("--file=src/a.rs"), "--file=src/a.rs");
+        assert_eq!(shell_quote("user@host:1,2+3%"), "user@host:1,2+3%");
+    }
+
+    #[test]
+    fn shell_quote_quotes_special_args() {
+        assert_eq!(shell_quote(""), "''");
+        assert_eq!(shell_quote("a b"), "'a b'");
+        assert_eq!(shell_quote("feat: x\n\nbody"), "'feat: x\n\nbody'");
+        assert_eq!(shell_quote("$HOME"), "'$HOME'");
+    }
+
+    #[test]
+    fn shell_quote_escapes_single_quotes() {
+        assert_eq!(shell_quote("don't"), r"'don'\''t'");
+        assert_eq!(shell_quote("'"), r"''\'''");
+    }
+
+    #[test]
+    fn argv_includes_program() {
+        let mut command = Command::new("git");
+        command.args(["commit", "-m", "fix: x"]);
+        assert_eq!(argv(&command), vec!["git", "commit", "-m", "fix: x"]);
+    }
+}
@@ -0,0 +1,155 @@
+use super::commit::{exit, run_prompts, with_raw, Stage};
+use crate::cli;
+use glint::commitlint;
+use glint::{prompt, Commit, Config, Git, LogItem};
+use std::io::{self, IsTerminal};
+use std::process::Command;
+
+enum Kind {
+    Fixup,
+    Squash,
+    Amend,
+}
+
+pub fn fixup(params: cli::Fixup, config: Config) {
+    let git = match Git::from_cwd() {
+        Ok(git) => git,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+
+    let kind = if params.squash {
+        Kind::Squash
+    } else if params.amend {
+        Kind::Amend
+    } else {
+        Kind::Fixup
+    };
+
+    let staged = match git.status() {
+        Ok(status) => status.staged_files(),
+        Err(err) => {
+            eprintln!("Failed to run git status: {}", err);
+            std::process::exit(1);
+        }
+    };
+    // An amend! commit can reword its target without changing any files.
+    if staged.is_empty() && !matches!(kind, Kind::Amend) {
+        eprintln!("No changes are staged. Stage the changes for the fixup first.");
+        std::process::exit(1);
+    }
+
+    if !io::stdin().is_terminal() {
+        eprintln!("glint fixup needs a terminal to choose the commit.");
+        std::process::exit(1);
+    }
+
+    let logs = match git.log_parsed(&[format!("-{}", params.num)]) {
+        Ok(logs) => logs,
+        Err(err) => {
+            eprintln!("Failed to read the recent commits: {}", err);
+            std::process::exit(1);
+        }
+    };
+    let logs: Vec<LogItem> = if params.all || staged.is_empty() {
+        logs
+    } else {
+        logs.into_iter()
+            .filter(|log| log.files.iter().any(|file| staged.contains(file)))
+            .collect()
+    };
+    if logs.is_empty() {
+        eprintln!(
+            "None of the last {} commits touch the staged files. Pass --all to choose from all of them.",
+            params.num
+        );
+        std::process::exit(1);
+    }
+
+    let title = match kind {
+        Kind::Fixup => "Fix up: ",
+        Kind::Squash => "Squash into: ",
+        Kind::Amend => "Amend: ",
+    };
+    let index = with_raw(
+        || match prompt::CommitPrompt::new(&config, title, &logs).run() {
+            prompt::CommitPromptResult::Commit(index) => Some(index),
+            prompt::CommitPromptResult::Terminate => exit(2),
+            prompt::CommitPromptResult::Escape => exit(0),
+        },
+    );
+    let index = match index {
+        Some(index) => index,
+        None => return,
+    };
+    let target = &logs[index];
+    let header = target.message.lines().next().unwrap_or("");
+
+    let mut git_args = params.git_args.clone();
+    let message = match kind {
+        Kind::Fixup => format!("fixup! {}", header),
+        Kind::Squash => format!("squash! {}", header),
+        Kind::Amend => {
+            let draft = match commitlint::parse(&target.message) {
+                Ok(parsed) => parsed.to_commit(),
+                Err(_) => Commit {
+                    message: header.to_string(),
+                    ..Default::default()
+                },
+            };
+            let answers = run_prompts(
+                &cli::Commit::default(),
+                &config,
+                &git,
+                None,
+                Stage::Type,
+                Some(draft),
+            );
+            let answers = match answers {
+                Some(answers) => answers,
+                None => std::process::exit(0),
+            };
+
+            if staged.is_empty() {
+                git_args.push("--allow-empty".to_string());
+            }
+            format!("amend! {}\n\n{}", header, answers.commit.build_message())
+        }
+    };
+
+    run("commit", git.commit(&message, &git_args));
+    println!("Commit successful.");
+
+    if params.rebase {
+        match git.autosquash(&target.commit) {
+            Ok(command) => run("rebase", command),
+            Err(err) => {
+                eprintln!(
+                    "Failed to run git. This is the best error I have:\n{:?}",
+                    err
+                );
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+/// Runs a git command, exiting with its status if it fails.
+fn run(name: &str, mut command: Command) {
+    match command.status() {
+        Ok(status) if status.success() => {}
+        Ok(status) => {
+            eprintln!("The {} command failed with {}", name, status);
+            std::process::exit(status.code().unwrap_or(1));
+        }
+        Err(err) => {
+            eprintln!(
+                "Failed to run git. This is the best error I have:\n{:?}",
+                err
+            );
+            std::process::exit(1);
+        }
+    }
+}
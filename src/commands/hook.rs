@@ -0,0 +1,212 @@
+use super::commit::{run_prompts, Stage};
+use super::lint::print_problems;
+use crate::cli;
+use glint::commitlint;
+use glint::{Config, Git};
+use std::env;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::Path;
+
+/// The hooks installed by `glint hook install`.
+const HOOKS: &[&str] = &["commit-msg", "prepare-commit-msg"];
+
+/// Identifies hooks written by glint, so we never move or delete anything else.
+const MARKER: &str = "# Installed by glint.";
+
+/// Appended to the name of a hook that existed before glint's was installed.
+const BACKUP_SUFFIX: &str = ".pre-glint";
+
+pub fn hook(params: cli::Hook, config: Config) {
+    let git = match Git::from_cwd() {
+        Ok(git) => git,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+
+    let result = match params {
+        cli::Hook::Install { force } => install(&git, force),
+        cli::Hook::Uninstall => uninstall(&git),
+        cli::Hook::Run { hook, args } => match hook.as_str() {
+            "commit-msg" => commit_msg(&args, &config),
+            "prepare-commit-msg" => prepare_commit_msg(&args, &config, &git),
+            _ => Ok(()),
+        },
+    };
+
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+}
+
+fn script(hook: &str, glint: &str) -> String {
+    // prepare-commit-msg prompts interactively, but git doesn't give hooks a stdin, so we
+    // reconnect it to the terminal when there is one.
+    let run = match hook {
+        "prepare-commit-msg" => format!(
+            r#"if [ -t 2 ] && (: < /dev/tty) 2> /dev/null; then
+    exec "$glint" hook run {hook} "$@" < /dev/tty
+fi"#,
+            hook = hook
+        ),
+        _ => format!(r#"exec "$glint" hook run {hook} "$@""#, hook = hook),
+    };
+
+    format!(
+        r#"#!/bin/sh
+{marker} Remove it with `glint hook uninstall`.
+glint="{glint}"
+[ -x "$glint" ] || glint=glint
+
+previous="$(dirname "$0")/{hook}{backup}"
+if [ -x "$previous" ]; then
+    "$previous" "$@" || exit $?
+fi
+
+{run}
+"#,
+        marker = MARKER,
+        glint = glint,
+        hook = hook,
+        backup = BACKUP_SUFFIX,
+        run = run,
+    )
+}
+
+fn is_glint_hook(path: &Path) -> bool {
+    fs::read_to_string(path)
+        .map(|contents| contents.contains(MARKER))
+        .unwrap_or(false)
+}
+
+#[cfg(unix)]
+fn set_executable(path: &Path) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+}
+
+#[cfg(not(unix))]
+fn set_executable(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+fn install(git: &Git, force: bool) -> io::Result<()> {
+    let dir = git.hooks_dir()?;
+    fs::create_dir_all(&dir)?;
+
+    let glint = env::current_exe()?;
+    let glint = glint.to_string_lossy();
+
+    for hook in HOOKS {
+        let path = dir.join(hook);
+        let backup = dir.join(format!("{}{}", hook, BACKUP_SUFFIX));
+
+        if path.exists() && !is_glint_hook(&path) {
+            if backup.exists() && !force {
+                return Err(io::Error::other(format!(
+                    "Both {} and {} exist. Remove one, or pass --force to replace the backup.",
+                    path.display(),
+                    backup.display()
+                )));
+            }
+
+            fs::rename(&path, &backup)?;
+            println!(
+                "Moved the existing {} hook to {}. It will run before glint's.",
+                hook,
+                backup.display()
+            );
+        }
+
+        fs::write(&path, script(hook, &glint))?;
+        set_executable(&path)?;
+        println!("Installed {}", path.display());
+    }
+
+    Ok(())
+}
+
+fn uninstall(git: &Git) -> io::Result<()> {
+    let dir = git.hooks_dir()?;
+
+    for hook in HOOKS {
+        let path = dir.join(hook);
+        let backup = dir.join(format!("{}{}", hook, BACKUP_SUFFIX));
+
+        if !is_glint_hook(&path) {
+            continue;
+        }
+
+        fs::remove_file(&path)?;
+        println!("Removed {}", path.display());
+
+        if backup.exists() {
+            fs::rename(&backup, &path)?;
+            println!("Restored the previous {} hook", hook);
+        }
+    }
+
+    Ok(())
+}
+
+/// Rejects the commit if the message has any errors.
+fn commit_msg(args: &[String], config: &Config) -> io::Result<()> {
+    let path = args
+        .first()
+        .ok_or_else(|| io::Error::other("commit-msg expects the message file"))?;
+    let message = commitlint::strip_comments(&fs::read_to_string(path)?);
+
+    // git aborts empty commits on its own.
+    if message.is_empty() {
+        return Ok(());
+    }
+
+    let problems = config.lint(&message);
+    if problems.is_empty() {
+        return Ok(());
+    }
+
+    let stderr = &mut io::stderr();
+    let (errors, _) = print_problems(stderr, &problems);
+
+    if errors > 0 {
+        eprintln!(
+            "\nThe commit message has {} errors. Nothing was committed.",
+            errors
+        );
+        eprintln!("(Use 'git commit --no-verify' to skip this check.)");
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+/// Runs the Type, Scope and Message prompts when git is about to open an editor with an
+/// empty message, and writes the result above git's comments.
+fn prepare_commit_msg(args: &[String], config: &Config, git: &Git) -> io::Result<()> {
+    let path = args
+        .first()
+        .ok_or_else(|| io::Error::other("prepare-commit-msg expects the message file"))?;
+
+    // A source means the message came from -m, a template, a merge, etc.
+    let has_source = args.get(1).is_some_and(|source| !source.is_empty());
+    if has_source || !io::stdin().is_terminal() {
+        return Ok(());
+    }
+
+    let contents = fs::read_to_string(path)?;
+    if !commitlint::strip_comments(&contents).is_empty() {
+        return Ok(());
+    }
+
+    let params = cli::Commit::default();
+    if let Some(answers) = run_prompts(&params, config, git, None, Stage::Type, None) {
+        let message = answers.commit.build_message();
+        fs::write(path, format!("{}\n{}", message, contents))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,141 @@
+use crate::cli;
+use crossterm::{
+    self as ct,
+    style::{Color, Print, SetForegroundColor as SetFg},
+};
+use glint::commitlint::{self, Level, Problem};
+use glint::{Config, Git};
+use std::fs::read_to_string;
+use std::io::{self, Read, Write as _Write};
+
+/// A message to check, along with the commit it came from (if any).
+struct Subject {
+    commit: Option<String>,
+    message: String,
+}
+
+fn read_subjects(params: &cli::Lint) -> io::Result<Vec<Subject>> {
+    if let Some(ref range) = params.range {
+        let git = match Git::from_cwd() {
+            Ok(git) => git,
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
+        };
+
+        let logs = git.log_parsed([range])?;
+        Ok(logs
+            .into_iter()
+            .map(|log| Subject {
+                commit: Some(log.commit),
+                message: log.message,
+            })
+            .collect())
+    } else if let Some(ref file) = params.file {
+        let message = commitlint::strip_comments(&read_to_string(file)?);
+        Ok(vec![Subject {
+            commit: None,
+            message,
+        }])
+    } else if params.stdin {
+        let mut message = String::new();
+        io::stdin().read_to_string(&mut message)?;
+        Ok(vec![Subject {
+            commit: None,
+            message: commitlint::strip_comments(&message),
+        }])
+    } else {
+        eprintln!("Nothing to lint. Pass --file, --stdin, or a revision range.");
+        std::process::exit(1);
+    }
+}
+
+/// Prints one line per problem. Returns the number of errors and warnings.
+pub fn print_problems(out: &mut impl io::Write, problems: &[Problem]) -> (usize, usize) {
+    let mut errors = 0;
+    let mut warnings = 0;
+
+    for problem in problems {
+        let (color, icon) = match problem.level {
+            Level::Error => {
+                errors += 1;
+                (Color::Red, "✖")
+            }
+            _ => {
+                warnings += 1;
+                (Color::Yellow, "⚠")
+            }
+        };
+
+        ct::queue!(
+            out,
+            SetFg(color),
+            Print(format!("  {} ", icon)),
+            SetFg(Color::Reset),
+            Print(&problem.message),
+            SetFg(Color::Grey),
+            Print(format!(" [{}]\n", problem.rule)),
+            SetFg(Color::Reset),
+        )
+        .unwrap();
+    }
+
+    (errors, warnings)
+}
+
+pub fn lint(params: cli::Lint, config: Config) {
+    let subjects = match read_subjects(&params) {
+        Ok(subjects) => subjects,
+        Err(err) => {
+            eprintln!("Failed to read commit messages: {}", err);
+            std::process::exit(1);
+        }
+    };
+
+    let mut errors = 0;
+    let mut warnings = 0;
+
+    let stdout = &mut io::stdout();
+    for subject in subjects {
+        let problems: Vec<_> = config
+            .lint(&subject.message)
+            .into_iter()
+            .filter(|problem| !params.quiet || problem.level == Level::Error)
+            .collect();
+
+        if problems.is_empty() {
+            continue;
+        }
+
+        let header = subject.message.lines().next().unwrap_or("");
+        if let Some(ref commit) = subject.commit {
+            ct::queue!(
+                stdout,
+                SetFg(Color::Yellow),
+                Print(&commit[..8]),
+                Print(" ")
+            )
+            .unwrap();
+        }
+        ct::queue!(stdout, SetFg(Color::Reset), Print(header), Print("\n")).unwrap();
+
+        let (e, w) = print_problems(stdout, &problems);
+        errors += e;
+        warnings += w;
+    }
+
+    if errors + warnings > 0 {
+        println!(
+            "\n{} problems ({} errors, {} warnings)",
+            errors + warnings,
+            errors,
+            warnings
+        );
+    }
+    stdout.flush().unwrap();
+
+    if errors > 0 {
+        std::process::exit(1);
+    }
+}
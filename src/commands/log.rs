@@ -8,7 +8,7 @@ use glint::{Config, Git};
 use std::io::Write as _Write;
 use std::{io, iter};
 
-pub fn log(params: cli::Log, _config: Config) {
+pub fn log(params: cli::Log, config: Config) {
     let git = match Git::from_cwd() {
         Ok(git) => git,
         Err(err) => {
@@ -28,17 +28,26 @@ pub fn log(params: cli::Log, _config: Config) {
 
     let stdout = &mut io::stdout();
     for log in logs {
-        let conv = log.as_conventional();
+        let conv = log.as_conventional().ok();
 
-        let scope = conv.clone().and_then(|c| c.scope.map(String::from));
-        let ty = match conv {
-            Some(ref conv) => conv.ty.to_string(),
-            None => "unknown".to_string(),
-        };
+        let scope = conv.as_ref().and_then(|c| c.scope);
+        let ty = conv.as_ref().map_or("unknown", |c| c.ty);
+        let emoji = conv.as_ref().and_then(|c| c.emoji);
+        let breaking = conv.as_ref().is_some_and(|c| c.breaking);
+        let ty_color = config
+            .find_type(ty)
+            .and_then(|ty| ty.color)
+            .unwrap_or(Color::Magenta);
 
+        // Everything after the header prefix, with the footers last.
         let message = match conv {
-            Some(ref conv) => conv.message,
-            None => &log.message,
+            Some(ref conv) => iter::once(conv.subject)
+                .chain(conv.body)
+                .map(String::from)
+                .chain(conv.footers.iter().map(|f| f.to_footer().to_string()))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            None => log.message.clone(),
         };
 
         let message = message
@@ -56,7 +65,8 @@ pub fn log(params: cli::Log, _config: Config) {
                 SetFg(Color::Yellow),
                 Print(log.commit[..8].to_string()),
                 Print(" "),
-                SetFg(Color::Magenta),
+                Print(emoji.map_or(String::new(), |emoji| format!("{} ", emoji))),
+                SetFg(ty_color),
                 Print(ty),
                 SetFg(Color::Grey),
                 Print(match scope {
@@ -64,15 +74,16 @@ pub fn log(params: cli::Log, _config: Config) {
                     None => "",
                 }),
                 SetFg(Color::Blue),
+                Print(scope.unwrap_or("")),
+                SetFg(Color::Grey),
                 Print(match scope {
-                    Some(ref scope) => scope,
+                    Some(_) => ")",
                     None => "",
                 }),
+                SetFg(Color::Red),
+                Print(if breaking { "!" } else { "" }),
                 SetFg(Color::Grey),
-                Print(match scope {
-                    Some(_) => "): ",
-                    None => ": ",
-                }),
+                Print(": "),
                 SetFg(Color::Reset),
                 Print(message),
                 Print("\n")
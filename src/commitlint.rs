@@ -1,7 +1,42 @@
+mod config;
+mod js_literal;
+mod lint;
+mod parse;
+
+pub use config::{CommitlintConfig, Level, Rule, When};
+pub use lint::{is_ignored, lint, strip_comments, Problem};
+pub use parse::{parse, ConventionalCommit, ConventionalFooter, ParseError, ParseErrorKind};
+
+/// The footer token that marks a breaking change.
+pub const BREAKING_CHANGE: &str = "BREAKING CHANGE";
+
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
 pub struct Commit {
+    /// A gitmoji or `:shortcode:` written before the type.
+    pub emoji: Option<String>,
     pub ty: String,
     pub scope: Option<String>,
+    /// Adds the `!` marker after the type/scope.
+    pub breaking: bool,
     pub message: String,
+    pub body: Option<String>,
+    pub footers: Vec<Footer>,
+}
+
+/// A git trailer style footer, e.g. `Refs: #123` or `BREAKING CHANGE: removes the v1 API`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Footer {
+    pub token: String,
+    pub separator: Separator,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Separator {
+    /// `Token: value`
+    Colon,
+    /// `Token #value`
+    Hash,
 }
 
 impl Commit {
@@ -18,6 +53,11 @@ impl Commit {
 
         let mut s = String::with_capacity(len);
 
+        if let Some(ref emoji) = self.emoji {
+            s.push_str(emoji);
+            s.push(' ');
+        }
+
         s.push_str(&self.ty);
 
         if let Some(ref scope) = self.scope {
@@ -26,10 +66,152 @@ impl Commit {
             s.push(')');
         }
 
+        if self.breaking {
+            s.push('!');
+        }
+
         s.push(':');
         s.push(' ');
         s.push_str(&self.message);
 
+        if let Some(body) = self
+            .body
+            .as_deref()
+            .map(str::trim)
+            .filter(|b| !b.is_empty())
+        {
+            s.push_str("\n\n");
+            s.push_str(body);
+        }
+
+        for (i, footer) in self.footers.iter().enumerate() {
+            s.push_str(if i == 0 { "\n\n" } else { "\n" });
+            s.push_str(&footer.to_string());
+        }
+
         s
     }
+
+    /// Marks the commit as breaking, and describes why in a `BREAKING CHANGE` footer.
+    pub fn add_breaking_change(&mut self, description: &str) {
+        self.breaking = true;
+        self.footers.push(Footer::new(BREAKING_CHANGE, description));
+    }
+}
+
+impl Footer {
+    pub fn new(token: impl Into<String>, value: impl Into<String>) -> Self {
+        Footer {
+            token: token.into(),
+            separator: Separator::Colon,
+            value: value.into(),
+        }
+    }
+
+    /// Parses `Token: value` or `Token #value`. The token may not contain whitespace, with
+    /// the exception of `BREAKING CHANGE`.
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        let (token, separator, value) = if let Some(value) = s
+            .strip_prefix(BREAKING_CHANGE)
+            .and_then(|rest| rest.strip_prefix(": "))
+        {
+            (BREAKING_CHANGE, Separator::Colon, value)
+        } else {
+            let end = s.find(|c: char| c == ':' || c.is_whitespace())?;
+            let (token, rest) = s.split_at(end);
+            if let Some(value) = rest.strip_prefix(": ") {
+                (token, Separator::Colon, value)
+            } else {
+                (token, Separator::Hash, rest.strip_prefix(" #")?)
+            }
+        };
+
+        if token.is_empty() || value.trim().is_empty() {
+            return None;
+        }
+
+        Some(Footer {
+            token: token.to_string(),
+            separator,
+            value: value.trim().to_string(),
+        })
+    }
+
+    pub fn is_breaking_change(&self) -> bool {
+        self.token == BREAKING_CHANGE || self.token == "BREAKING-CHANGE"
+    }
+}
+
+impl std::str::FromStr for Footer {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Footer::parse(s).ok_or_else(|| {
+            format!(
+                "'{}' isn't a footer. Expected 'Token: value' or 'Token #value'.",
+                s
+            )
+        })
+    }
+}
+
+impl std::fmt::Display for Footer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.separator {
+            Separator::Colon => write!(f, "{}: {}", self.token, self.value),
+            Separator::Hash => write!(f, "{} #{}", self.token, self.value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Commit, Footer, Separator};
+
+    #[test]
+    fn build_header_only() {
+        let commit = Commit {
+            ty: "fix".into(),
+            scope: Some("client".into()),
+            message: "handle errors".into(),
+            ..Default::default()
+        };
+        assert_eq!(commit.build_message(), "fix(client): handle errors");
+    }
+
+    #[test]
+    fn build_full() {
+        let mut commit = Commit {
+            ty: "feat".into(),
+            scope: Some("api".into()),
+            message: "drop v1 endpoints".into(),
+            body: Some("The v1 endpoints were deprecated last year.\n".into()),
+            footers: vec![Footer::parse("Refs #123").unwrap()],
+            ..Default::default()
+        };
+        commit.add_breaking_change("v1 clients must upgrade");
+
+        assert_eq!(
+            commit.build_message(),
+            "feat(api)!: drop v1 endpoints\n\n\
+             The v1 endpoints were deprecated last year.\n\n\
+             Refs #123\n\
+             BREAKING CHANGE: v1 clients must upgrade"
+        );
+    }
+
+    #[test]
+    fn parse_footers() {
+        let footer = Footer::parse("Reviewed-by: Z").unwrap();
+        assert_eq!(footer.token, "Reviewed-by");
+        assert_eq!(footer.separator, Separator::Colon);
+        assert_eq!(footer.value, "Z");
+
+        let footer = Footer::parse("BREAKING CHANGE: everything").unwrap();
+        assert!(footer.is_breaking_change());
+
+        assert_eq!(Footer::parse("not a footer"), None);
+        assert_eq!(Footer::parse("Refs:"), None);
+    }
 }
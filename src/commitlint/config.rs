@@ -0,0 +1,223 @@
+use super::js_literal;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+
+/// The config file names commitlint itself searches for, in the same order.
+const CONFIG_FILES: &[&str] = &[
+    ".commitlintrc",
+    ".commitlintrc.json",
+    ".commitlintrc.yaml",
+    ".commitlintrc.yml",
+    ".commitlintrc.js",
+    ".commitlintrc.cjs",
+    ".commitlintrc.mjs",
+    ".commitlintrc.ts",
+    "commitlint.config.js",
+    "commitlint.config.cjs",
+    "commitlint.config.mjs",
+    "commitlint.config.ts",
+];
+
+const CONVENTIONAL_PRESET: &str = "@commitlint/config-conventional";
+
+/// The rules section of a commitlint config. Only the static parts are understood, so
+/// rules defined by functions are left out.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CommitlintConfig {
+    pub rules: BTreeMap<String, Rule>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule {
+    pub level: Level,
+    pub when: When,
+    pub value: Value,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Level {
+    Disabled,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum When {
+    Always,
+    Never,
+}
+
+impl CommitlintConfig {
+    /// Returns the commitlint config in `dir`, including a `commitlint` key in package.json.
+    pub fn find(dir: &Path) -> Option<PathBuf> {
+        CONFIG_FILES
+            .iter()
+            .map(|name| dir.join(name))
+            .find(|path| path.is_file())
+            .or_else(|| {
+                let path = dir.join("package.json");
+                let contents = read_to_string(&path).ok()?;
+                let json: Value = serde_json::from_str(&contents).ok()?;
+                json.get("commitlint").map(|_| path)
+            })
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let contents = read_to_string(path).map_err(|err| err.to_string())?;
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let extension = path.extension().unwrap_or_default().to_string_lossy();
+
+        let value: Value = match extension.as_ref() {
+            _ if name == "package.json" => {
+                let json: Value = serde_json::from_str(&contents).map_err(|e| e.to_string())?;
+                json.get("commitlint").cloned().unwrap_or(Value::Null)
+            }
+            "json" => serde_json::from_str(&contents).map_err(|e| e.to_string())?,
+            "yaml" | "yml" => serde_yaml::from_str(&contents).map_err(|e| e.to_string())?,
+            "js" | "cjs" | "mjs" | "ts" => js_literal::parse_exported_object(&contents)?,
+            // `.commitlintrc` may be either JSON or YAML, and YAML is a superset of JSON.
+            _ => serde_yaml::from_str(&contents).map_err(|e| e.to_string())?,
+        };
+
+        Ok(CommitlintConfig::from_value(&value))
+    }
+
+    /// Reads the `rules` of a parsed config, applying the rules of
+    /// `@commitlint/config-conventional` first if it's listed in `extends`.
+    pub fn from_value(value: &Value) -> Self {
+        let extends_conventional = match value.get("extends") {
+            Some(Value::String(s)) => s == CONVENTIONAL_PRESET,
+            Some(Value::Array(items)) => items.iter().any(|item| item == CONVENTIONAL_PRESET),
+            _ => false,
+        };
+
+        let mut config = if extends_conventional {
+            CommitlintConfig::conventional()
+        } else {
+            CommitlintConfig::default()
+        };
+
+        if let Some(Value::Object(rules)) = value.get("rules") {
+            for (name, rule) in rules {
+                if let Some(rule) = Rule::from_value(rule) {
+                    config.rules.insert(name.clone(), rule);
+                }
+            }
+        }
+
+        config
+    }
+
+    /// The rules of `@commitlint/config-conventional`.
+    pub fn conventional() -> Self {
+        let value = serde_json::json!({
+            "rules": {
+                "body-leading-blank": [1, "always"],
+                "body-max-line-length": [2, "always", 100],
+                "footer-leading-blank": [1, "always"],
+                "footer-max-line-length": [2, "always", 100],
+                "header-max-length": [2, "always", 100],
+                "subject-case": [2, "never", ["sentence-case", "start-case", "pascal-case", "upper-case"]],
+                "subject-empty": [2, "never"],
+                "subject-full-stop": [2, "never", "."],
+                "type-case": [2, "always", "lower-case"],
+                "type-empty": [2, "never"],
+                "type-enum": [2, "always", [
+                    "build", "chore", "ci", "docs", "feat", "fix",
+                    "perf", "refactor", "revert", "style", "test",
+                ]],
+            }
+        });
+
+        CommitlintConfig::from_value(&value)
+    }
+
+    /// Returns the rule if it's present and not disabled.
+    pub fn rule(&self, name: &str) -> Option<&Rule> {
+        self.rules
+            .get(name)
+            .filter(|rule| rule.level != Level::Disabled)
+    }
+
+    /// The allowed values of an enabled `*-enum` rule that uses "always".
+    pub fn allowed(&self, name: &str) -> Option<Vec<String>> {
+        let rule = self.rule(name).filter(|rule| rule.when == When::Always)?;
+        rule.strings()
+    }
+
+    pub fn types(&self) -> Option<Vec<String>> {
+        self.allowed("type-enum")
+    }
+
+    pub fn scopes(&self) -> Option<Vec<String>> {
+        self.allowed("scope-enum")
+    }
+}
+
+impl Rule {
+    /// Parses the `[level, when, value]` array form. Returns None for anything else,
+    /// such as a rule we couldn't evaluate statically.
+    fn from_value(value: &Value) -> Option<Self> {
+        let items = value.as_array()?;
+        let level = match items.first()?.as_u64()? {
+            0 => Level::Disabled,
+            1 => Level::Warning,
+            2 => Level::Error,
+            _ => return None,
+        };
+        let when = match items.get(1).and_then(Value::as_str) {
+            Some("never") => When::Never,
+            _ => When::Always,
+        };
+        let value = items.get(2).cloned().unwrap_or(Value::Null);
+
+        Some(Rule { level, when, value })
+    }
+
+    /// The value as a list of strings, also accepting a single string.
+    pub fn strings(&self) -> Option<Vec<String>> {
+        match &self.value {
+            Value::String(s) => Some(vec![s.clone()]),
+            Value::Array(items) => Some(
+                items
+                    .iter()
+                    .filter_map(|item| item.as_str().map(String::from))
+                    .collect(),
+            ),
+            _ => None,
+        }
+    }
+
+    pub fn number(&self) -> Option<usize> {
+        self.value.as_u64().map(|n| n as usize)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CommitlintConfig, Level, When};
+
+    #[test]
+    fn rules_override_extended_preset() {
+        let yaml = r#"
+extends:
+  - "@commitlint/config-conventional"
+rules:
+  type-enum: [2, always, [feat, fix, release]]
+  scope-enum: [1, always, [client, server]]
+  header-max-length: [0]
+"#;
+        let value = serde_yaml::from_str(yaml).unwrap();
+        let config = CommitlintConfig::from_value(&value);
+
+        assert_eq!(config.types().unwrap(), vec!["feat", "fix", "release"]);
+        assert_eq!(config.scopes().unwrap(), vec!["client", "server"]);
+        assert!(config.rule("header-max-length").is_none());
+
+        let subject_empty = config.rule("subject-empty").unwrap();
+        assert_eq!(subject_empty.level, Level::Error);
+        assert_eq!(subject_empty.when, When::Never);
+    }
+}
@@ -0,0 +1,364 @@
+//! Just enough of a JavaScript parser to read the exported object literal from a
+//! `commitlint.config.js`. Anything dynamic (function calls, spreads, variables) is
+//! skipped and read as `null`, which leaves the rule it belongs to unset.
+use serde_json::{Map, Number, Value};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Punct(char),
+    Str(String),
+    Num(f64),
+    Ident(String),
+}
+
+fn tokenize(src: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut chars = src.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '/' if chars.peek() == Some(&'/') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut prev = ' ';
+                for c in chars.by_ref() {
+                    if prev == '*' && c == '/' {
+                        break;
+                    }
+                    prev = c;
+                }
+            }
+            '\'' | '"' | '`' => {
+                let quote = c;
+                let mut s = String::new();
+                loop {
+                    match chars.next() {
+                        Some('\\') => match chars.next() {
+                            Some('n') => s.push('\n'),
+                            Some('t') => s.push('\t'),
+                            Some(c) => s.push(c),
+                            None => return Err("unterminated string".into()),
+                        },
+                        Some(c) if c == quote => break,
+                        Some(c) => s.push(c),
+                        None => return Err("unterminated string".into()),
+                    }
+                }
+                tokens.push(Token::Str(s));
+            }
+            c if c.is_ascii_digit() => {
+                let mut s = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    if c.is_ascii_digit() || c == '.' || c == '_' {
+                        s.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                let num = s
+                    .replace('_', "")
+                    .parse()
+                    .map_err(|_| format!("invalid number {}", s))?;
+                tokens.push(Token::Num(num));
+            }
+            c if c.is_alphabetic() || c == '_' || c == '$' => {
+                let mut s = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    if c.is_alphanumeric() || c == '_' || c == '$' {
+                        s.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(Token::Ident(s));
+            }
+            c => tokens.push(Token::Punct(c)),
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<&'a Token> {
+        let token = self.tokens.get(self.pos);
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(&Token::Punct(c)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Skips an expression we don't understand, stopping before the `,` or closing
+    /// bracket that ends it.
+    fn skip_expression(&mut self) {
+        let mut depth = 0;
+        while let Some(token) = self.peek() {
+            match token {
+                Token::Punct('{') | Token::Punct('[') | Token::Punct('(') => depth += 1,
+                Token::Punct('}') | Token::Punct(']') | Token::Punct(')') if depth == 0 => break,
+                Token::Punct('}') | Token::Punct(']') | Token::Punct(')') => depth -= 1,
+                Token::Punct(',') | Token::Punct(';') if depth == 0 => break,
+                _ => {}
+            }
+            self.pos += 1;
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        let start = self.pos;
+        let value = match self.next() {
+            Some(Token::Punct('{')) => self.object()?,
+            Some(Token::Punct('[')) => self.array()?,
+            Some(Token::Str(s)) => Value::String(s.clone()),
+            Some(Token::Num(n)) => number(*n),
+            Some(Token::Punct('-')) => match self.next() {
+                Some(Token::Num(n)) => number(-*n),
+                _ => Value::Null,
+            },
+            Some(Token::Ident(ident)) => match ident.as_str() {
+                "true" => Value::Bool(true),
+                "false" => Value::Bool(false),
+                "null" | "undefined" => Value::Null,
+                _ => self.member_expression(ident),
+            },
+            _ => Value::Null,
+        };
+
+        // Whatever follows a complete value (e.g. a call or an operator) makes it dynamic.
+        match self.peek() {
+            None
+            | Some(Token::Punct(','))
+            | Some(Token::Punct(';'))
+            | Some(Token::Punct('}'))
+            | Some(Token::Punct(']'))
+            | Some(Token::Punct(')')) => Ok(value),
+            Some(_) => {
+                self.pos = start;
+                self.skip_expression();
+                Ok(Value::Null)
+            }
+        }
+    }
+
+    /// Resolves `RuleConfigSeverity.Error` and friends from `@commitlint/types`.
+    fn member_expression(&mut self, ident: &str) -> Value {
+        if ident != "RuleConfigSeverity" || !self.eat('.') {
+            return Value::Null;
+        }
+
+        match self.next() {
+            Some(Token::Ident(level)) => match level.as_str() {
+                "Disabled" => Value::from(0),
+                "Warning" => Value::from(1),
+                "Error" => Value::from(2),
+                _ => Value::Null,
+            },
+            _ => Value::Null,
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, String> {
+        let mut items = vec![];
+        loop {
+            if self.eat(']') {
+                return Ok(Value::Array(items));
+            }
+            items.push(self.value()?);
+            if self.eat(']') {
+                return Ok(Value::Array(items));
+            } else if !self.eat(',') {
+                return Err("expected ',' or ']' in array".into());
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Value, String> {
+        let mut map = Map::new();
+        loop {
+            if self.eat('}') {
+                return Ok(Value::Object(map));
+            }
+
+            let key = match self.next() {
+                Some(Token::Ident(s)) | Some(Token::Str(s)) => Some(s.clone()),
+                Some(Token::Num(n)) => Some(n.to_string()),
+                _ => None,
+            };
+
+            match key {
+                Some(key) if self.eat(':') => {
+                    let value = self.value()?;
+                    map.insert(key, value);
+                }
+                // Spreads, computed keys, methods, etc.
+                _ => self.skip_expression(),
+            }
+
+            if self.eat('}') {
+                return Ok(Value::Object(map));
+            } else if !self.eat(',') {
+                return Err("expected ',' or '}' in object".into());
+            }
+        }
+    }
+}
+
+fn number(n: f64) -> Value {
+    if n.fract() == 0.0 && n.abs() < i64::MAX as f64 {
+        Value::from(n as i64)
+    } else {
+        Number::from_f64(n)
+            .map(Value::Number)
+            .unwrap_or(Value::Null)
+    }
+}
+
+/// Finds the object exported by `module.exports = ...` or `export default ...` and converts
+/// it to JSON. Exports that are a variable name or a `defineConfig({ ... })`-style call
+/// are followed to the object literal.
+pub fn parse_exported_object(src: &str) -> Result<Value, String> {
+    let tokens = tokenize(src)?;
+    let ident = |s: &str| Token::Ident(s.into());
+
+    let export_end = tokens
+        .windows(4)
+        .position(|w| {
+            w[..]
+                == [
+                    ident("module"),
+                    Token::Punct('.'),
+                    ident("exports"),
+                    Token::Punct('='),
+                ]
+        })
+        .map(|i| i + 4)
+        .or_else(|| {
+            tokens
+                .windows(2)
+                .position(|w| w[..] == [ident("export"), ident("default")])
+                .map(|i| i + 2)
+        })
+        .ok_or("no `module.exports` or `export default` found")?;
+
+    let mut start = export_end;
+    // The variables followed so far, so that aliases of each other don't loop forever.
+    let mut visited: Vec<&str> = vec![];
+    loop {
+        match tokens.get(start) {
+            Some(Token::Punct('{')) => break,
+            // A call wrapping the object, e.g. `defineConfig({ ... })`
+            Some(Token::Ident(_)) if tokens.get(start + 1) == Some(&Token::Punct('(')) => {
+                start += 2;
+            }
+            // A variable declared elsewhere in the file
+            Some(Token::Ident(name)) => {
+                if visited.contains(&name.as_str()) {
+                    return Err("the exported variable isn't assigned an object literal".into());
+                }
+                visited.push(name);
+
+                let name = ident(name);
+                start = tokens
+                    .windows(2)
+                    .position(|w| w[0] == name && w[1] != Token::Punct(';'))
+                    .and_then(|i| {
+                        tokens[i..]
+                            .iter()
+                            .position(|t| *t == Token::Punct('='))
+                            .map(|offset| i + offset + 1)
+                    })
+                    .filter(|&i| i < export_end)
+                    .ok_or("the exported variable isn't assigned an object literal")?;
+            }
+            _ => return Err("the export isn't an object literal".into()),
+        }
+    }
+
+    let mut parser = Parser {
+        tokens: &tokens[start + 1..],
+        pos: 0,
+    };
+    parser.object()
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_exported_object;
+    use serde_json::json;
+
+    #[test]
+    fn module_exports() {
+        let src = r#"
+            // A comment
+            module.exports = {
+              extends: ['@commitlint/config-conventional'],
+              rules: {
+                'type-enum': [2, 'always', ['feat', "fix", `docs`,]],
+                /* block */ 'scope-case': [RuleConfigSeverity.Warning, 'always', 'kebab-case'],
+                'scope-enum': async (ctx) => [2, 'always', await getScopes(ctx)],
+              },
+            };
+        "#;
+
+        assert_eq!(
+            parse_exported_object(src).unwrap(),
+            json!({
+                "extends": ["@commitlint/config-conventional"],
+                "rules": {
+                    "type-enum": [2, "always", ["feat", "fix", "docs"]],
+                    "scope-case": [1, "always", "kebab-case"],
+                    "scope-enum": null,
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn export_default_variable() {
+        let src = r#"
+            import type { UserConfig } from '@commitlint/types';
+            const Configuration: UserConfig = { rules: { 'header-max-length': [0] } };
+            export default Configuration;
+        "#;
+
+        assert_eq!(
+            parse_exported_object(src).unwrap(),
+            json!({ "rules": { "header-max-length": [0] } })
+        );
+    }
+
+    #[test]
+    fn variable_alias_cycle() {
+        let src = "const a = b; const b = a; module.exports = a;";
+
+        assert_eq!(
+            parse_exported_object(src).unwrap_err(),
+            "the exported variable isn't assigned an object literal"
+        );
+    }
+}
@@ -0,0 +1,398 @@
+use super::parse::{parse, sections, ConventionalCommit};
+use super::{CommitlintConfig, Level, Rule, When};
+use std::fmt;
+
+/// A rule violation found by `lint`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Problem {
+    pub rule: String,
+    pub level: Level,
+    pub message: String,
+}
+
+/// Not a commitlint rule. Reports messages whose header can't be parsed, in place of the
+/// type and subject rules. It's on unless the config disables it.
+const HEADER_FORMAT: &str = "header-format";
+
+/// The parts of a message that the rules look at.
+#[derive(Debug, Default)]
+struct Parts<'a> {
+    header: &'a str,
+    ty: &'a str,
+    scope: Option<&'a str>,
+    subject: &'a str,
+    body: Option<&'a str>,
+    footer: Option<&'a str>,
+    body_leading_blank: bool,
+    footer_leading_blank: bool,
+}
+
+impl<'a> Parts<'a> {
+    fn new(message: &'a str, header: Option<&ConventionalCommit<'a>>) -> Self {
+        let sections = sections(message);
+        // Whether there's a blank line between two offsets.
+        let blank_between =
+            |start: usize, end: usize| message[start..end].matches('\n').count() > 1;
+
+        let mut parts = Parts {
+            header: &message[sections.header.clone()],
+            body: sections.body.clone().map(|range| &message[range]),
+            footer: sections.footer.clone().map(|range| &message[range]),
+            ..Default::default()
+        };
+
+        if let Some(ref body) = sections.body {
+            parts.body_leading_blank = blank_between(sections.header.end, body.start);
+        }
+        if let Some(ref footer) = sections.footer {
+            let previous_end = sections.body.as_ref().unwrap_or(&sections.header).end;
+            parts.footer_leading_blank = blank_between(previous_end, footer.start);
+        }
+
+        if let Some(commit) = header {
+            parts.ty = commit.ty;
+            parts.scope = commit.scope;
+            parts.subject = commit.subject;
+        }
+
+        parts
+    }
+
+    fn body_lines(&self) -> Vec<&'a str> {
+        self.body
+            .map(|body| body.lines().collect())
+            .unwrap_or_default()
+    }
+
+    fn footer_lines(&self) -> Vec<&'a str> {
+        self.footer
+            .map(|footer| footer.lines().collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Messages that commitlint never checks, such as merge commits and fixups.
+pub fn is_ignored(message: &str) -> bool {
+    const PREFIXES: &[&str] = &[
+        "Merge pull request",
+        "Merge branch",
+        "Merge remote-tracking branch",
+        "Merge tag",
+        "Revert \"",
+        "fixup! ",
+        "squash! ",
+        "amend! ",
+        "Initial commit",
+        "Auto-merged ",
+    ];
+
+    PREFIXES.iter().any(|prefix| message.starts_with(prefix))
+}
+
+/// Removes git's comment lines and everything below the scissors line, leaving the
+/// message as it will be committed.
+pub fn strip_comments(message: &str) -> String {
+    let mut lines = vec![];
+    for line in message.lines() {
+        if line.starts_with("# ------------------------ >8 ------------------------") {
+            break;
+        }
+        if !line.starts_with('#') {
+            lines.push(line.trim_end());
+        }
+    }
+
+    lines.join("\n").trim().to_string()
+}
+
+/// Checks `message` against every enabled rule that glint understands. Returns an empty
+/// list for ignored messages.
+pub fn lint(message: &str, config: &CommitlintConfig) -> Vec<Problem> {
+    let mut problems = vec![];
+
+    if is_ignored(message) {
+        return problems;
+    }
+
+    let header_format = config
+        .rules
+        .get(HEADER_FORMAT)
+        .map_or(Level::Error, |rule| rule.level);
+
+    let commit = match parse(message) {
+        Ok(commit) => Some(commit),
+        Err(err) if header_format != Level::Disabled => {
+            problems.push(Problem {
+                rule: HEADER_FORMAT.to_string(),
+                level: header_format,
+                message: format!("header must be 'type(scope): subject', but {}", err.kind),
+            });
+            None
+        }
+        Err(_) => None,
+    };
+    let parts = Parts::new(message, commit.as_ref());
+    // The type and subject rules would only repeat the header-format problem.
+    let skip_header_rules = commit.is_none() && header_format != Level::Disabled;
+
+    for (name, rule) in config.rules.iter() {
+        if rule.level == Level::Disabled {
+            continue;
+        }
+
+        if skip_header_rules
+            && (name.starts_with("type-")
+                || name.starts_with("scope-")
+                || name.starts_with("subject-"))
+        {
+            continue;
+        }
+
+        let check = match name.as_str() {
+            "type-enum" => check_enum("type", Some(parts.ty), rule),
+            "type-case" => check_case("type", Some(parts.ty), rule),
+            "type-empty" => check_empty("type", parts.ty, rule),
+            "scope-enum" => check_enum("scope", parts.scope, rule),
+            "scope-case" => check_case("scope", parts.scope, rule),
+            "scope-empty" => check_empty("scope", parts.scope.unwrap_or(""), rule),
+            "subject-case" => check_case("subject", Some(parts.subject), rule),
+            "subject-empty" => check_empty("subject", parts.subject, rule),
+            "subject-full-stop" => check_full_stop(parts.subject, rule),
+            "subject-max-length" => check_max_length("subject", parts.subject, rule),
+            "header-max-length" => check_max_length("header", parts.header, rule),
+            "body-leading-blank" if parts.body.is_some() => {
+                check_blank("body", parts.body_leading_blank, rule)
+            }
+            "body-empty" => check_empty("body", parts.body.unwrap_or(""), rule),
+            "body-max-line-length" => check_max_line_length("body", &parts.body_lines(), rule),
+            "footer-leading-blank" if parts.footer.is_some() => {
+                check_blank("footer", parts.footer_leading_blank, rule)
+            }
+            "footer-max-line-length" => {
+                check_max_line_length("footer", &parts.footer_lines(), rule)
+            }
+            _ => Ok(()),
+        };
+
+        if let Err(message) = check {
+            problems.push(Problem {
+                rule: name.clone(),
+                level: rule.level,
+                message,
+            });
+        }
+    }
+
+    problems.sort_by_key(|problem| std::cmp::Reverse(problem.level));
+    problems
+}
+
+type Check = Result<(), String>;
+
+/// Applies the rule's always/never to a condition which is true when the "always" form holds.
+fn expect(rule: &Rule, holds: bool, always: String, never: String) -> Check {
+    match (rule.when, holds) {
+        (When::Always, false) => Err(always),
+        (When::Never, true) => Err(never),
+        _ => Ok(()),
+    }
+}
+
+fn check_enum(field: &str, value: Option<&str>, rule: &Rule) -> Check {
+    let allowed = rule.strings().unwrap_or_default();
+    let values: Vec<&str> = match value {
+        Some(value) if !value.is_empty() => split_scopes(value),
+        _ => return Ok(()),
+    };
+
+    let holds = values.iter().all(|v| allowed.iter().any(|a| a == v));
+    let list = allowed.join(", ");
+    expect(
+        rule,
+        holds,
+        format!("{} must be one of [{}]", field, list),
+        format!("{} must not be one of [{}]", field, list),
+    )
+}
+
+fn check_case(field: &str, value: Option<&str>, rule: &Rule) -> Check {
+    let cases = rule.strings().unwrap_or_default();
+    let value = match value {
+        Some(value) if !value.is_empty() => value,
+        _ => return Ok(()),
+    };
+
+    // Each of multiple scopes has to match, but other fields are checked as a whole.
+    let values = if field == "scope" {
+        split_scopes(value)
+    } else {
+        vec![value]
+    };
+    let holds = values
+        .iter()
+        .all(|v| cases.iter().any(|case| is_case(v, case)));
+    let list = cases.join(", ");
+    expect(
+        rule,
+        holds,
+        format!("{} must be {}", field, list),
+        format!("{} must not be {}", field, list),
+    )
+}
+
+fn check_empty(field: &str, value: &str, rule: &Rule) -> Check {
+    expect(
+        rule,
+        value.trim().is_empty(),
+        format!("{} must be empty", field),
+        format!("{} may not be empty", field),
+    )
+}
+
+fn check_full_stop(subject: &str, rule: &Rule) -> Check {
+    let stop = rule.value.as_str().unwrap_or(".");
+    expect(
+        rule,
+        subject.ends_with(stop),
+        format!("subject must end with full stop '{}'", stop),
+        format!("subject may not end with full stop '{}'", stop),
+    )
+}
+
+fn check_max_length(field: &str, value: &str, rule: &Rule) -> Check {
+    let max = match rule.number() {
+        Some(max) => max,
+        None => return Ok(()),
+    };
+    let len = value.chars().count();
+
+    if len > max {
+        Err(format!(
+            "{} must not be longer than {} characters, current length is {}",
+            field, max, len
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+fn check_max_line_length(field: &str, lines: &[&str], rule: &Rule) -> Check {
+    let max = match rule.number() {
+        Some(max) => max,
+        None => return Ok(()),
+    };
+
+    match lines.iter().find(|line| line.chars().count() > max) {
+        Some(_) => Err(format!(
+            "{}'s lines must not be longer than {} characters",
+            field, max
+        )),
+        None => Ok(()),
+    }
+}
+
+fn check_blank(field: &str, blank: bool, rule: &Rule) -> Check {
+    expect(
+        rule,
+        blank,
+        format!("{} must have leading blank line", field),
+        format!("{} must not have leading blank line", field),
+    )
+}
+
+fn split_scopes(value: &str) -> Vec<&str> {
+    value
+        .split([',', '/', '|'])
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+fn is_case(s: &str, case: &str) -> bool {
+    let is_lower_word = |w: &str| w.chars().all(|c| !c.is_uppercase());
+    let starts_upper = |w: &str| w.chars().next().is_some_and(char::is_uppercase);
+    let no_separators = |w: &str| !w.contains(['-', '_', ' ']);
+
+    match case {
+        "lower-case" | "lowercase" => s == s.to_lowercase(),
+        "upper-case" | "uppercase" => s == s.to_uppercase(),
+        "kebab-case" => is_lower_word(s) && !s.contains(['_', ' ']),
+        "snake-case" => is_lower_word(s) && !s.contains(['-', ' ']),
+        "camel-case" => !starts_upper(s) && no_separators(s),
+        "pascal-case" => starts_upper(s) && no_separators(s),
+        "sentence-case" | "sentencecase" => {
+            let mut chars = s.chars();
+            chars.next().is_none_or(|c| !c.is_lowercase()) && is_lower_word(chars.as_str())
+        }
+        "start-case" | "startcase" => s.split(' ').filter(|w| !w.is_empty()).all(starts_upper),
+        _ => true,
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} [{}]", self.message, self.rule)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{lint, strip_comments};
+    use crate::commitlint::{CommitlintConfig, Level};
+
+    fn rules(problems: &[super::Problem]) -> Vec<&str> {
+        problems.iter().map(|p| p.rule.as_str()).collect()
+    }
+
+    #[test]
+    fn valid_message() {
+        let config = CommitlintConfig::conventional();
+        let message = "feat(api): add a thing\n\nSome body\n\nRefs: #123";
+        assert_eq!(lint(message, &config), vec![]);
+    }
+
+    #[test]
+    fn reports_each_rule() {
+        let config = CommitlintConfig::conventional();
+
+        let problems = lint("Feature: Add a thing.", &config);
+        assert_eq!(
+            rules(&problems),
+            vec![
+                "subject-case",
+                "subject-full-stop",
+                "type-case",
+                "type-enum"
+            ]
+        );
+
+        let problems = lint("fix: something\nbody\nRefs: #1", &config);
+        assert_eq!(
+            rules(&problems),
+            vec!["body-leading-blank", "footer-leading-blank"]
+        );
+        assert!(problems.iter().all(|p| p.level == Level::Warning));
+
+        let problems = lint(&format!("fix: {}", "a".repeat(100)), &config);
+        assert_eq!(rules(&problems), vec!["header-max-length"]);
+
+        let problems = lint("just a sentence", &config);
+        assert_eq!(rules(&problems), vec!["header-format"]);
+        assert_eq!(
+            problems[0].message,
+            "header must be 'type(scope): subject', but expected ':' after the type, scope or '!'"
+        );
+    }
+
+    #[test]
+    fn ignores_merges() {
+        let config = CommitlintConfig::conventional();
+        assert_eq!(lint("Merge branch 'main' into feature", &config), vec![]);
+    }
+
+    #[test]
+    fn strips_git_comments() {
+        let message = "fix: a\n\n# Please enter the commit message\n# ------------------------ >8 ------------------------\ndiff --git";
+        assert_eq!(strip_comments(message), "fix: a");
+    }
+}
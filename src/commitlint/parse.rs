@@ -0,0 +1,432 @@
+//! A parser for the [Conventional Commits 1.0] format.
+//!
+//! [Conventional Commits 1.0]: https://www.conventionalcommits.org/en/v1.0.0/
+use super::{Commit, Footer, Separator, BREAKING_CHANGE};
+use crate::gitmoji;
+use std::fmt;
+use std::ops::Range;
+
+/// A parsed message. Always contains slices of the original message.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ConventionalCommit<'a> {
+    /// A gitmoji or `:shortcode:` before the type.
+    pub emoji: Option<&'a str>,
+    pub ty: &'a str,
+    pub scope: Option<&'a str>,
+    /// True if the header has the `!` marker or there's a `BREAKING CHANGE` footer.
+    pub breaking: bool,
+    pub subject: &'a str,
+    pub body: Option<&'a str>,
+    pub footers: Vec<ConventionalFooter<'a>>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ConventionalFooter<'a> {
+    pub token: &'a str,
+    pub separator: Separator,
+    /// May span multiple lines.
+    pub value: &'a str,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    /// The byte range of the message that caused the error.
+    pub span: Range<usize>,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ParseErrorKind {
+    EmptyHeader,
+    MissingType,
+    InvalidTypeChar(char),
+    UnclosedScope,
+    EmptyScope,
+    MissingColon,
+    MissingSpace,
+    EmptySubject,
+}
+
+/// Byte ranges of the three sections of a message. The body and footer exclude their
+/// surrounding blank lines.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) struct Sections {
+    pub header: Range<usize>,
+    pub body: Option<Range<usize>>,
+    pub footer: Option<Range<usize>>,
+}
+
+impl<'a> ConventionalCommit<'a> {
+    /// Converts to the owned form, e.g. to edit and rebuild the message.
+    pub fn to_commit(&self) -> Commit {
+        Commit {
+            emoji: self.emoji.map(String::from),
+            ty: self.ty.to_string(),
+            scope: self.scope.map(String::from),
+            breaking: self.breaking,
+            message: self.subject.to_string(),
+            body: self.body.map(String::from),
+            footers: self
+                .footers
+                .iter()
+                .map(ConventionalFooter::to_footer)
+                .collect(),
+        }
+    }
+}
+
+impl<'a> ConventionalFooter<'a> {
+    pub fn is_breaking_change(&self) -> bool {
+        self.token == BREAKING_CHANGE || self.token == "BREAKING-CHANGE"
+    }
+
+    pub fn to_footer(&self) -> Footer {
+        Footer {
+            token: self.token.to_string(),
+            separator: self.separator,
+            value: self.value.to_string(),
+        }
+    }
+}
+
+/// Returns the length of the footer token and separator at the start of `line`, if it
+/// starts a footer.
+fn footer_prefix(line: &str) -> Option<(usize, Separator)> {
+    for token in &[BREAKING_CHANGE, "BREAKING-CHANGE"] {
+        if line.starts_with(token) && line[token.len()..].starts_with(": ") {
+            return Some((token.len(), Separator::Colon));
+        }
+    }
+
+    let token_len = line
+        .find(|c: char| !(c.is_alphanumeric() || c == '-'))
+        .unwrap_or(line.len());
+
+    if token_len == 0 {
+        None
+    } else if line[token_len..].starts_with(": ") {
+        Some((token_len, Separator::Colon))
+    } else if line[token_len..].starts_with(" #") {
+        Some((token_len, Separator::Hash))
+    } else {
+        None
+    }
+}
+
+/// Lines of `message` with their byte offsets.
+fn lines_with_offsets(message: &str) -> Vec<(usize, &str)> {
+    let mut offset = 0;
+    message
+        .split('\n')
+        .map(|line| {
+            let start = offset;
+            offset += line.len() + 1;
+            (start, line.trim_end_matches('\r'))
+        })
+        .collect()
+}
+
+/// Splits a message into header, body and footer. Like git trailers, footers are only
+/// looked for in the last paragraph, so a body paragraph that happens to begin with
+/// `Word: ` isn't mistaken for one.
+pub(crate) fn sections(message: &str) -> Sections {
+    let lines = lines_with_offsets(message);
+    let header_end = lines.first().map(|(_, line)| line.len()).unwrap_or(0);
+
+    let is_blank = |i: usize| lines[i].1.trim().is_empty();
+    let paragraph_start = |i: usize| i == 1 || (i > 1 && is_blank(i - 1)) && !is_blank(i);
+
+    let mut footer_start = None;
+    for i in (1..lines.len()).rev() {
+        if is_blank(i) {
+            continue;
+        }
+
+        if footer_prefix(lines[i].1).is_some() {
+            footer_start = Some(i);
+        }
+        if paragraph_start(i) {
+            break;
+        }
+    }
+
+    // Returns the byte range covering the non-blank lines in `range`.
+    let span = |range: Range<usize>| {
+        let first = range.clone().find(|&i| !is_blank(i))?;
+        let last = range.rev().find(|&i| !is_blank(i))?;
+        Some(lines[first].0..lines[last].0 + lines[last].1.len())
+    };
+
+    let body_end = footer_start.unwrap_or(lines.len());
+    Sections {
+        header: 0..header_end,
+        body: span(1..body_end),
+        footer: footer_start.and_then(|start| span(start..lines.len())),
+    }
+}
+
+fn parse_header(header: &str) -> Result<(&str, Option<&str>, bool, &str), ParseError> {
+    let error = |kind, span| Err(ParseError { kind, span });
+
+    if header.trim().is_empty() {
+        return error(ParseErrorKind::EmptyHeader, 0..header.len());
+    }
+
+    let ty_end = header
+        .find(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_'))
+        .unwrap_or(header.len());
+    let ty = &header[..ty_end];
+    let mut rest = &header[ty_end..];
+    let mut pos = ty_end;
+
+    let next = rest.chars().next();
+    if ty.is_empty() {
+        return match next {
+            Some(c) if c == '(' || c == ':' || c == '!' => error(ParseErrorKind::MissingType, 0..0),
+            Some(c) => error(ParseErrorKind::InvalidTypeChar(c), 0..c.len_utf8()),
+            None => error(ParseErrorKind::MissingType, 0..0),
+        };
+    }
+
+    let mut scope = None;
+    if rest.starts_with('(') {
+        let close = match rest.find(')') {
+            Some(close) => close,
+            None => return error(ParseErrorKind::UnclosedScope, pos..header.len()),
+        };
+        let inner = &rest[1..close];
+        if inner.trim().is_empty() {
+            return error(ParseErrorKind::EmptyScope, pos..pos + close + 1);
+        }
+        scope = Some(inner);
+        rest = &rest[close + 1..];
+        pos += close + 1;
+    }
+
+    let breaking = rest.starts_with('!');
+    if breaking {
+        rest = &rest[1..];
+        pos += 1;
+    }
+
+    if !rest.starts_with(':') {
+        return match rest.chars().next() {
+            // A character that can't be part of a type, e.g. `feat.x: y`
+            Some(c) if scope.is_none() && !breaking && c != ' ' => {
+                error(ParseErrorKind::InvalidTypeChar(c), pos..pos + c.len_utf8())
+            }
+            _ => error(ParseErrorKind::MissingColon, pos..pos),
+        };
+    }
+
+    if !rest[1..].starts_with(' ') {
+        return error(ParseErrorKind::MissingSpace, pos + 1..pos + 1);
+    }
+
+    let subject = rest[1..].trim();
+    if subject.is_empty() {
+        return error(ParseErrorKind::EmptySubject, pos + 1..header.len());
+    }
+
+    Ok((ty, scope, breaking, subject))
+}
+
+fn parse_footers(footer: &str) -> Vec<ConventionalFooter<'_>> {
+    let mut footers: Vec<ConventionalFooter> = vec![];
+    // The start of the current footer's value, and the end of its last non-blank line.
+    let mut current: Option<(usize, usize)> = None;
+
+    for (offset, line) in lines_with_offsets(footer) {
+        match footer_prefix(line) {
+            Some((token_len, separator)) => {
+                if let (Some((start, end)), Some(last)) = (current, footers.last_mut()) {
+                    last.value = &footer[start..end];
+                }
+
+                let value_start = offset + token_len + 2;
+                footers.push(ConventionalFooter {
+                    token: &line[..token_len],
+                    separator,
+                    value: "",
+                });
+                current = Some((value_start, offset + line.len()));
+            }
+            None if !line.trim().is_empty() => {
+                if let Some((_, ref mut end)) = current {
+                    *end = offset + line.len();
+                }
+            }
+            None => {}
+        }
+    }
+
+    if let (Some((start, end)), Some(last)) = (current, footers.last_mut()) {
+        last.value = &footer[start..end];
+    }
+
+    for footer in footers.iter_mut() {
+        footer.value = footer.value.trim();
+    }
+
+    footers
+}
+
+/// Parses `message` according to the Conventional Commits spec, allowing a gitmoji
+/// before the type.
+///
+/// Only the header is strict. The body and footers may be separated by blank lines, but
+/// it's not an error if they aren't (that's left to the linter).
+pub fn parse(message: &str) -> Result<ConventionalCommit<'_>, ParseError> {
+    let sections = sections(message);
+    let header = &message[sections.header.clone()];
+    let (emoji, offset) = match gitmoji::split_prefix(header) {
+        Some((emoji, offset)) => (Some(emoji), offset),
+        None => (None, 0),
+    };
+    let (ty, scope, bang, subject) = parse_header(&header[offset..]).map_err(|err| ParseError {
+        span: err.span.start + offset..err.span.end + offset,
+        ..err
+    })?;
+
+    let body = sections.body.map(|range| &message[range]);
+    let footers = sections
+        .footer
+        .map(|range| parse_footers(&message[range]))
+        .unwrap_or_default();
+
+    let breaking = bang || footers.iter().any(ConventionalFooter::is_breaking_change);
+
+    Ok(ConventionalCommit {
+        emoji,
+        ty,
+        scope,
+        breaking,
+        subject,
+        body,
+        footers,
+    })
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseErrorKind::EmptyHeader => write!(f, "the header is empty"),
+            ParseErrorKind::MissingType => write!(f, "the header must start with a type"),
+            ParseErrorKind::InvalidTypeChar(c) => {
+                write!(f, "'{}' isn't allowed in the type", c)
+            }
+            ParseErrorKind::UnclosedScope => write!(f, "the scope is missing its ')'"),
+            ParseErrorKind::EmptyScope => write!(f, "the scope is empty"),
+            ParseErrorKind::MissingColon => {
+                write!(f, "expected ':' after the type, scope or '!'")
+            }
+            ParseErrorKind::MissingSpace => write!(f, "expected a space after the ':'"),
+            ParseErrorKind::EmptySubject => write!(f, "the description is empty"),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (at {}..{})",
+            self.kind, self.span.start, self.span.end
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[cfg(test)]
+mod test {
+    use super::{parse, ConventionalFooter, ParseErrorKind};
+    use crate::commitlint::Separator;
+
+    #[test]
+    fn header_only() {
+        let commit = parse("fix: correct minor typos").unwrap();
+        assert_eq!(commit.ty, "fix");
+        assert_eq!(commit.scope, None);
+        assert!(!commit.breaking);
+        assert_eq!(commit.subject, "correct minor typos");
+        assert_eq!(commit.body, None);
+        assert!(commit.footers.is_empty());
+    }
+
+    #[test]
+    fn full_message() {
+        let message = "feat(api)!: drop v1\n\nFirst paragraph.\n\nNote: this is body\ntoo.\n\nReviewed-by: Z\nRefs #123\nBREAKING CHANGE: v1 clients\n  must upgrade\n";
+        let commit = parse(message).unwrap();
+        assert_eq!(commit.ty, "feat");
+        assert_eq!(commit.scope, Some("api"));
+        assert!(commit.breaking);
+        assert_eq!(commit.subject, "drop v1");
+        assert_eq!(
+            commit.body,
+            Some("First paragraph.\n\nNote: this is body\ntoo.")
+        );
+        assert_eq!(
+            commit.footers,
+            vec![
+                ConventionalFooter {
+                    token: "Reviewed-by",
+                    separator: Separator::Colon,
+                    value: "Z"
+                },
+                ConventionalFooter {
+                    token: "Refs",
+                    separator: Separator::Hash,
+                    value: "123"
+                },
+                ConventionalFooter {
+                    token: "BREAKING CHANGE",
+                    separator: Separator::Colon,
+                    value: "v1 clients\n  must upgrade"
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn gitmoji_prefix() {
+        let commit = parse("✨ feat(ui): add a button").unwrap();
+        assert_eq!(commit.emoji, Some("✨"));
+        assert_eq!((commit.ty, commit.scope), ("feat", Some("ui")));
+
+        let commit = parse(":bug:  fix: handle errors").unwrap();
+        assert_eq!(commit.emoji, Some(":bug:"));
+        assert_eq!(commit.subject, "handle errors");
+        assert_eq!(
+            commit.to_commit().build_message(),
+            ":bug: fix: handle errors"
+        );
+
+        let err = parse("✨ feat(ui: x").unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::UnclosedScope);
+        assert_eq!(err.span.start, "✨ feat".len());
+    }
+
+    #[test]
+    fn breaking_footer_without_marker() {
+        let commit = parse("chore: x\n\nBREAKING CHANGE: y").unwrap();
+        assert!(commit.breaking);
+        assert_eq!(commit.body, None);
+    }
+
+    #[test]
+    fn header_errors() {
+        let kind = |message| parse(message).unwrap_err().kind;
+        assert_eq!(kind(""), ParseErrorKind::EmptyHeader);
+        assert_eq!(kind(": x"), ParseErrorKind::MissingType);
+        assert_eq!(kind("feat(api: x"), ParseErrorKind::UnclosedScope);
+        assert_eq!(kind("feat(): x"), ParseErrorKind::EmptyScope);
+        assert_eq!(kind("just a sentence"), ParseErrorKind::MissingColon);
+        assert_eq!(kind("feat:x"), ParseErrorKind::MissingSpace);
+        assert_eq!(kind("feat: "), ParseErrorKind::EmptySubject);
+        assert_eq!(kind("feat.x: y"), ParseErrorKind::InvalidTypeChar('.'));
+
+        let err = parse("feat(api: x").unwrap_err();
+        assert_eq!(err.span, 4..11);
+    }
+}
@@ -1,10 +1,135 @@
-use crate::Figlet;
+use crate::changelog::{self, ChangelogConfig, Section};
+use crate::commitlint::{lint, parse, Problem};
+use crate::gitmoji::{self, GitmojiStyle};
+use crate::glob;
+use crate::scopes::ScopePath;
+use crate::{CommitlintConfig, Figlet};
+use crossterm::style::Color;
+use serde::de::{self, Deserializer, MapAccess, Visitor};
+use serde::Deserialize;
+use std::env;
+use std::fmt;
+use std::fs::read_to_string;
 use std::io;
+use std::path::{Path, PathBuf};
+
+/// File names checked, in order, in the repo root and each of its ancestors.
+const REPO_CONFIG_FILES: &[&str] = &[".glintrc", "glint.toml"];
 
 #[derive(Debug, Clone)]
 pub struct Config {
-    pub types: Vec<String>,
+    pub types: Vec<CommitType>,
+    /// Chooses a type as soon as the input matches only one.
+    pub auto_submit_type: bool,
+    /// Lets the Type prompt use the input as a type that isn't in `types`.
+    pub custom_types: bool,
+    /// When set, custom types must match this glob, e.g. `[a-z]*`.
+    pub custom_type_pattern: Option<String>,
+    /// When set, headers start with the type's emoji.
+    pub gitmoji: Option<GitmojiStyle>,
+    /// When non-empty, the scopes offered (and required) by the Scope prompt.
+    pub scopes: Vec<String>,
+    /// Maps changed files to scopes. The first matching mapping wins.
+    pub scope_paths: Vec<ScopePath>,
     pub figlet_file: Option<String>,
+    /// Shows diffs in the Files prompt instead of the built-in viewer: `git` for git's
+    /// pager, or a shell command such as `delta`. `builtin` is the same as leaving it unset.
+    pub pager: Option<String>,
+    /// The repo's commitlint config, if it has one.
+    pub commitlint: Option<CommitlintConfig>,
+    pub changelog: ChangelogConfig,
+    pub stages: Stages,
+}
+
+/// A commit type offered by the Type prompt, and how it's presented.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CommitType {
+    pub name: String,
+    /// Shown next to the type in the Type prompt.
+    pub description: Option<String>,
+    /// An emoji or `:shortcode:`, used in gitmoji mode.
+    pub emoji: Option<String>,
+    /// Used for the type in the Type prompt and `glint log`.
+    pub color: Option<Color>,
+    /// The changelog section listing commits of this type, instead of the default one.
+    pub section: Option<String>,
+    /// Leaves commits of this type out of the changelog, unless they're breaking changes.
+    pub hidden: bool,
+}
+
+/// The optional stages of `glint commit`, which follow the Message prompt. They're all off
+/// unless turned on in the config.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct Stages {
+    /// Asks whether the commit is a breaking change, and for a description of it.
+    pub breaking: bool,
+    /// A multi-line editor for the body.
+    pub body: bool,
+    /// Adds footers such as issue references.
+    pub footers: bool,
+}
+
+/// The contents of a single config file. Every key is optional so that a repo-level
+/// file only needs to mention the settings it wants to change.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    types: Option<Vec<TypeFile>>,
+    auto_submit_type: Option<bool>,
+    custom_types: Option<bool>,
+    custom_type_pattern: Option<String>,
+    gitmoji: Option<GitmojiStyle>,
+    scopes: Option<Vec<String>>,
+    scope_paths: Option<Vec<ScopePath>>,
+    figlet_file: Option<String>,
+    pager: Option<String>,
+    changelog: Option<ChangelogFile>,
+    stages: Option<StagesFile>,
+}
+
+/// An entry in `types`: either just the name, or a table describing the type.
+#[derive(Debug)]
+enum TypeFile {
+    Name(String),
+    Table(TypeTable),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TypeTable {
+    name: String,
+    description: Option<String>,
+    emoji: Option<String>,
+    #[serde(default, deserialize_with = "crate::color::deserialize")]
+    color: Option<Color>,
+    section: Option<String>,
+    #[serde(default)]
+    hidden: bool,
+}
+
+/// The `[changelog]` table.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ChangelogFile {
+    format: Option<changelog::Format>,
+    sections: Option<Vec<changelog::Section>>,
+    commit_url: Option<String>,
+}
+
+/// The `[stages]` table.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StagesFile {
+    breaking: Option<bool>,
+    body: Option<bool>,
+    footers: Option<bool>,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, toml::de::Error),
+    Commitlint(PathBuf, String),
 }
 
 impl Config {
@@ -14,30 +139,511 @@ impl Config {
             None => Ok(Figlet::default()),
         }
     }
+
+    /// Builds the effective config by layering, on top of the defaults:
+    ///
+    /// 1. the user config (`~/.config/glint/config.toml`)
+    /// 2. the types and scopes from a commitlint config in `repo_root`
+    /// 3. the first `.glintrc` or `glint.toml` found walking up from `repo_root`
+    pub fn load(repo_root: Option<&Path>) -> Result<Self, ConfigError> {
+        let mut config = Config::default();
+
+        if let Some(path) = Config::user_config_path().filter(|path| path.is_file()) {
+            config.apply_file(&path)?;
+        }
+
+        if let Some(path) = repo_root.and_then(CommitlintConfig::find) {
+            let commitlint = CommitlintConfig::from_file(&path)
+                .map_err(|err| ConfigError::Commitlint(path, err))?;
+            config.apply_commitlint(commitlint);
+        }
+
+        if let Some(path) = repo_root.and_then(Config::find_repo_config) {
+            config.apply_file(&path)?;
+        }
+
+        Ok(config)
+    }
+
+    /// Returns the first repo-level config file in `start` or any of its ancestors.
+    pub fn find_repo_config(start: &Path) -> Option<PathBuf> {
+        start
+            .ancestors()
+            .flat_map(|dir| REPO_CONFIG_FILES.iter().map(move |name| dir.join(name)))
+            .find(|path| path.is_file())
+    }
+
+    /// The location of the user-level config, respecting `$XDG_CONFIG_HOME`.
+    pub fn user_config_path() -> Option<PathBuf> {
+        let config_home = env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+
+        Some(config_home.join("glint").join("config.toml"))
+    }
+
+    /// The rules used to validate messages: the repo's commitlint config if it has one,
+    /// otherwise `@commitlint/config-conventional` using the configured types.
+    pub fn lint_rules(&self) -> CommitlintConfig {
+        match self.commitlint {
+            Some(ref commitlint) => commitlint.clone(),
+            None => {
+                let mut rules = CommitlintConfig::conventional();
+                if let Some(rule) = rules.rules.get_mut("type-enum") {
+                    let names: Vec<String> = self.types.iter().map(|ty| ty.name.clone()).collect();
+                    rule.value = names.into();
+                }
+                rules
+            }
+        }
+    }
+
+    /// Checks `message` against `lint_rules`. When custom types are allowed, a type that
+    /// `type-enum` rejects is checked like the Type prompt checks it instead, so that new
+    /// types pass only if they match `custom_type_pattern`. A repo's commitlint config
+    /// still has the last word, so that glint accepts the same messages as commitlint.
+    pub fn lint(&self, message: &str) -> Vec<Problem> {
+        let mut problems = lint(message, &self.lint_rules());
+        if !self.custom_types || self.commitlint.is_some() {
+            return problems;
+        }
+
+        let ty = match parse(message) {
+            Ok(commit) => commit.ty,
+            Err(_) => return problems,
+        };
+        if let Some(i) = problems.iter().position(|p| p.rule == "type-enum") {
+            match self.check_type(ty) {
+                Ok(()) => {
+                    problems.remove(i);
+                }
+                Err(err) => problems[i].message = err,
+            }
+        }
+
+        problems
+    }
+
+    pub fn find_type(&self, name: &str) -> Option<&CommitType> {
+        self.types.iter().find(|ty| ty.name == name)
+    }
+
+    /// The changelog sections for `format`, after moving the types that name a `section`
+    /// into it and removing the hidden ones.
+    pub fn changelog_sections(&self, format: changelog::Format) -> Vec<Section> {
+        let mut sections = ChangelogConfig {
+            format,
+            ..self.changelog.clone()
+        }
+        .sections();
+
+        for ty in self
+            .types
+            .iter()
+            .filter(|ty| ty.hidden || ty.section.is_some())
+        {
+            for section in sections.iter_mut() {
+                section.types.retain(|name| *name != ty.name);
+            }
+
+            let title = match ty.section {
+                Some(ref title) if !ty.hidden => title,
+                _ => continue,
+            };
+            match sections.iter_mut().find(|section| section.title == *title) {
+                Some(section) => section.types.push(ty.name.clone()),
+                None => sections.push(Section {
+                    title: title.clone(),
+                    types: vec![ty.name.clone()],
+                }),
+            }
+        }
+
+        sections
+    }
+
+    /// Checks a type that wasn't picked from the list: it must be configured, or be a
+    /// valid custom type when those are allowed.
+    pub fn check_type(&self, ty: &str) -> Result<(), String> {
+        if self.find_type(ty).is_some() {
+            return Ok(());
+        }
+        if !self.custom_types {
+            return Err(format!("'{}' isn't one of the configured types", ty));
+        }
+
+        if let Err(err) = crate::commitlint::parse(&format!("{}: subject", ty)) {
+            return Err(format!("'{}' can't be used as a type: {}", ty, err.kind));
+        }
+
+        match self.custom_type_pattern {
+            Some(ref pattern) if !glob::matches(pattern, ty) => Err(format!(
+                "'{}' doesn't match the pattern for new types, '{}'",
+                ty, pattern
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks that each of the scopes separated by `,` or `|` is allowed.
+    pub fn check_scope(&self, scope: &str) -> Result<(), String> {
+        if self.scopes.is_empty() {
+            return Ok(());
+        }
+
+        match scope
+            .split([',', '|'])
+            .filter(|scope| !scope.is_empty())
+            .find(|scope| !self.scopes.iter().any(|allowed| allowed == scope))
+        {
+            Some(scope) => Err(format!("Unknown scope '{}'", scope)),
+            None => Ok(()),
+        }
+    }
+
+    /// The prefix for a commit of type `ty` in gitmoji mode.
+    pub fn gitmoji_for(&self, ty: &str) -> Option<String> {
+        let style = self.gitmoji?;
+        let emoji = self.find_type(ty)?.emoji.as_deref()?;
+        Some(gitmoji::format(emoji, style))
+    }
+
+    /// The type named `name`, keeping what's known about it from an earlier layer.
+    fn type_named(&self, name: String) -> CommitType {
+        self.find_type(&name)
+            .cloned()
+            .unwrap_or_else(|| CommitType::new(name))
+    }
+
+    fn apply_file(&mut self, path: &Path) -> Result<(), ConfigError> {
+        let file = ConfigFile::read(path)?;
+        self.apply(file, path.parent().unwrap_or_else(|| Path::new(".")));
+        Ok(())
+    }
+
+    /// Uses the `type-enum` and `scope-enum` rules, so glint offers exactly what the
+    /// linter accepts.
+    fn apply_commitlint(&mut self, commitlint: CommitlintConfig) {
+        if let Some(types) = commitlint.types() {
+            self.types = types.into_iter().map(|ty| self.type_named(ty)).collect();
+        }
+
+        if let Some(scopes) = commitlint.scopes() {
+            self.scopes = scopes;
+        }
+
+        self.commitlint = Some(commitlint);
+    }
+
+    /// Overrides any settings present in `file`. Relative paths are resolved against `dir`,
+    /// the directory containing the config file.
+    fn apply(&mut self, file: ConfigFile, dir: &Path) {
+        if let Some(types) = file.types {
+            self.types = types
+                .into_iter()
+                .map(|ty| match ty {
+                    TypeFile::Name(name) => self.type_named(name),
+                    TypeFile::Table(table) => CommitType {
+                        name: table.name,
+                        description: table.description,
+                        emoji: table.emoji,
+                        color: table.color,
+                        section: table.section,
+                        hidden: table.hidden,
+                    },
+                })
+                .collect();
+        }
+
+        if let Some(auto_submit_type) = file.auto_submit_type {
+            self.auto_submit_type = auto_submit_type;
+        }
+
+        if let Some(custom_types) = file.custom_types {
+            self.custom_types = custom_types;
+        }
+
+        if let Some(custom_type_pattern) = file.custom_type_pattern {
+            self.custom_type_pattern = Some(custom_type_pattern);
+        }
+
+        if let Some(gitmoji) = file.gitmoji {
+            self.gitmoji = Some(gitmoji);
+        }
+
+        if let Some(scopes) = file.scopes {
+            self.scopes = scopes;
+        }
+
+        if let Some(scope_paths) = file.scope_paths {
+            self.scope_paths = scope_paths;
+        }
+
+        if let Some(figlet_file) = file.figlet_file {
+            self.figlet_file = Some(dir.join(figlet_file).to_string_lossy().into_owned());
+        }
+
+        if let Some(pager) = file.pager {
+            self.pager = Some(pager);
+        }
+
+        if let Some(changelog) = file.changelog {
+            if let Some(format) = changelog.format {
+                self.changelog.format = format;
+            }
+            if let Some(sections) = changelog.sections {
+                self.changelog.sections = Some(sections);
+            }
+            if let Some(commit_url) = changelog.commit_url {
+                self.changelog.commit_url = Some(commit_url);
+            }
+        }
+
+        if let Some(stages) = file.stages {
+            if let Some(breaking) = stages.breaking {
+                self.stages.breaking = breaking;
+            }
+            if let Some(body) = stages.body {
+                self.stages.body = body;
+            }
+            if let Some(footers) = stages.footers {
+                self.stages.footers = footers;
+            }
+        }
+    }
+}
+
+impl ConfigFile {
+    fn read(path: &Path) -> Result<Self, ConfigError> {
+        let contents = read_to_string(path).map_err(|err| ConfigError::Io(path.into(), err))?;
+        toml::from_str(&contents).map_err(|err| ConfigError::Parse(path.into(), err))
+    }
 }
 
 impl Default for Config {
     fn default() -> Self {
         Config {
             types: vec![
-                "build",
-                "ci",
-                "chore",
-                "docs",
-                "feat",
-                "fix",
-                "perf",
-                "refactor",
-                "revert",
-                "style",
-                "test",
-                "enhance",
-                "improvement",
+                (
+                    "build",
+                    "Changes to the build system or dependencies",
+                    "📦️",
+                    Color::White,
+                ),
+                (
+                    "ci",
+                    "Changes to the CI configuration and scripts",
+                    "👷",
+                    Color::Blue,
+                ),
+                (
+                    "chore",
+                    "Other changes that don't touch the source or tests",
+                    "🔧",
+                    Color::Yellow,
+                ),
+                ("docs", "Documentation only", "📝", Color::DarkBlue),
+                ("feat", "A new feature", "✨", Color::Blue),
+                ("fix", "A bug fix", "🐛", Color::Red),
+                ("perf", "Makes something faster", "⚡️", Color::Green),
+                (
+                    "refactor",
+                    "Restructures code without changing behavior",
+                    "♻️",
+                    Color::DarkCyan,
+                ),
+                ("revert", "Reverts an earlier commit", "⏪️", Color::DarkRed),
+                (
+                    "style",
+                    "Formatting, whitespace and the like",
+                    "🎨",
+                    Color::Cyan,
+                ),
+                ("test", "Adds or corrects tests", "✅", Color::Magenta),
+                ("enhance", "Extends an existing feature", "🚸", Color::Blue),
+                (
+                    "improvement",
+                    "Improves existing behavior, but isn't a feature",
+                    "👌",
+                    Color::Green,
+                ),
             ]
             .into_iter()
-            .map(String::from)
+            .map(|(name, description, emoji, color)| CommitType {
+                description: Some(description.to_string()),
+                emoji: Some(emoji.to_string()),
+                color: Some(color),
+                ..CommitType::new(name.to_string())
+            })
             .collect(),
+            auto_submit_type: true,
+            custom_types: false,
+            custom_type_pattern: None,
+            gitmoji: None,
+            scopes: vec![],
+            scope_paths: vec![],
             figlet_file: None,
+            pager: None,
+            commitlint: None,
+            changelog: ChangelogConfig::default(),
+            stages: Stages::default(),
+        }
+    }
+}
+
+impl CommitType {
+    pub fn new(name: String) -> Self {
+        CommitType {
+            name,
+            description: None,
+            emoji: None,
+            color: None,
+            section: None,
+            hidden: false,
         }
     }
 }
+
+impl<'de> Deserialize<'de> for TypeFile {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct TypeFileVisitor;
+
+        impl<'de> Visitor<'de> for TypeFileVisitor {
+            type Value = TypeFile;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a type name or a table with a `name`")
+            }
+
+            fn visit_str<E: de::Error>(self, name: &str) -> Result<TypeFile, E> {
+                Ok(TypeFile::Name(name.to_string()))
+            }
+
+            fn visit_map<M: MapAccess<'de>>(self, map: M) -> Result<TypeFile, M::Error> {
+                let table = TypeTable::deserialize(de::value::MapAccessDeserializer::new(map))?;
+                Ok(TypeFile::Table(table))
+            }
+        }
+
+        deserializer.deserialize_any(TypeFileVisitor)
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(path, err) => {
+                write!(f, "Failed to read config file {}: {}", path.display(), err)
+            }
+            ConfigError::Parse(path, err) => {
+                write!(f, "Invalid config file {}: {}", path.display(), err)
+            }
+            ConfigError::Commitlint(path, err) => write!(
+                f,
+                "Unable to read commitlint config {}: {}",
+                path.display(),
+                err
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CommitType, Config, ConfigFile};
+    use crate::changelog::Format;
+    use crate::CommitlintConfig;
+    use crossterm::style::Color;
+    use std::path::Path;
+
+    #[test]
+    fn apply_overrides_only_present_keys() {
+        let mut config = Config::default();
+        let file: ConfigFile = toml::from_str(r#"figlet_file = "fonts/big.flf""#).unwrap();
+        config.apply(file, Path::new("/repo"));
+
+        assert_eq!(config.types, Config::default().types);
+        assert_eq!(config.figlet_file.as_deref(), Some("/repo/fonts/big.flf"));
+
+        let file: ConfigFile = toml::from_str(r#"types = ["feat", "fix"]"#).unwrap();
+        config.apply(file, Path::new("/other"));
+
+        let names: Vec<&str> = config.types.iter().map(|ty| ty.name.as_str()).collect();
+        assert_eq!(names, vec!["feat", "fix"]);
+        assert_eq!(config.figlet_file.as_deref(), Some("/repo/fonts/big.flf"));
+    }
+
+    #[test]
+    fn lint_checks_custom_types() {
+        let type_enum = |config: &Config, message: &str| {
+            config
+                .lint(message)
+                .into_iter()
+                .find(|problem| problem.rule == "type-enum")
+                .map(|problem| problem.message)
+        };
+
+        let mut config = Config::default();
+        assert!(type_enum(&config, "wip: thing").is_some());
+
+        config.custom_types = true;
+        config.custom_type_pattern = Some("[a-z]*".into());
+        assert_eq!(type_enum(&config, "feat: thing"), None);
+        assert_eq!(type_enum(&config, "wip: thing"), None);
+        assert_eq!(
+            type_enum(&config, "WIP: thing").as_deref(),
+            Some("'WIP' doesn't match the pattern for new types, '[a-z]*'")
+        );
+
+        // A commitlint config's type-enum decides, like it would in commitlint.
+        config.commitlint = Some(CommitlintConfig::conventional());
+        assert_eq!(type_enum(&config, "feat: thing"), None);
+        assert!(type_enum(&config, "wip: thing").is_some());
+    }
+
+    #[test]
+    fn parse_errors_name_the_key() {
+        let err = toml::from_str::<ConfigFile>("types = 3").unwrap_err();
+        assert!(err.to_string().contains("`types`"), "{}", err);
+
+        let err = toml::from_str::<ConfigFile>("typs = []").unwrap_err();
+        assert!(err.to_string().contains("`typs`"), "{}", err);
+    }
+
+    #[test]
+    fn types_keep_known_metadata() {
+        let mut config = Config::default();
+        let file: ConfigFile = toml::from_str(
+            r#"
+            types = [
+                "feat",
+                { name = "deps", description = "Dependency updates", color = "dark_yellow", section = "Changed" },
+                { name = "chore", hidden = true },
+            ]
+            "#,
+        )
+        .unwrap();
+        config.apply(file, Path::new("/repo"));
+
+        assert_eq!(config.types[0], Config::default().types[4]);
+        assert_eq!(
+            config.types[1],
+            CommitType {
+                description: Some("Dependency updates".to_string()),
+                color: Some(Color::DarkYellow),
+                section: Some("Changed".to_string()),
+                ..CommitType::new("deps".to_string())
+            }
+        );
+        assert!(config.types[2].hidden);
+
+        let sections = config.changelog_sections(Format::KeepAChangelog);
+        assert_eq!(sections[1].title, "Changed");
+        assert_eq!(sections[1].types.last().map(String::as_str), Some("deps"));
+
+        let err = toml::from_str::<ConfigFile>(r#"types = [{ name = "x", color = "pink" }]"#)
+            .unwrap_err();
+        assert!(err.to_string().contains("pink"), "{}", err);
+    }
+}
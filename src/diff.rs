@@ -0,0 +1,551 @@
+//! Unified diffs, as printed by `git diff`, and patches for staging parts of them.
+
+use std::fmt::Write as _;
+use std::ops::Range;
+
+/// The changes to one file.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FileDiff {
+    /// The lines before the first hunk, from `diff --git` to `+++`.
+    pub header: Vec<String>,
+    /// The path in the old version, or `None` for a new file.
+    pub old_path: Option<String>,
+    /// The path in the new version, or `None` for a deleted file.
+    pub new_path: Option<String>,
+    /// Empty for binary files and changes that only affect the mode.
+    pub hunks: Vec<Hunk>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Hunk {
+    pub old_start: usize,
+    pub old_len: usize,
+    pub new_start: usize,
+    pub new_len: usize,
+    /// The text after the second `@@`, usually the enclosing function.
+    pub section: String,
+    pub lines: Vec<Line>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Line {
+    pub kind: LineKind,
+    /// The line without its `+`, `-` or ` ` prefix.
+    pub text: String,
+    /// Followed by `\ No newline at end of file`.
+    pub no_newline: bool,
+}
+
+/// Changes chosen from the diffs of some files, to be staged with `git apply --cached`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Patch {
+    pub files: Vec<String>,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum LineKind {
+    Context,
+    Added,
+    Removed,
+}
+
+impl Line {
+    pub fn is_change(&self) -> bool {
+        self.kind != LineKind::Context
+    }
+}
+
+impl LineKind {
+    fn prefix(self) -> char {
+        match self {
+            LineKind::Context => ' ',
+            LineKind::Added => '+',
+            LineKind::Removed => '-',
+        }
+    }
+}
+
+/// Parses the output of `git diff`, which may cover several files. Lines that don't fit the
+/// format are skipped.
+pub fn parse(diff: &str) -> Vec<FileDiff> {
+    let mut files: Vec<FileDiff> = vec![];
+
+    for line in diff.lines() {
+        if line.starts_with("diff ") {
+            files.push(FileDiff {
+                header: vec![line.to_string()],
+                old_path: None,
+                new_path: None,
+                hunks: vec![],
+            });
+            continue;
+        }
+
+        let file = match files.last_mut() {
+            Some(file) => file,
+            None => continue,
+        };
+
+        if line.starts_with("@@") {
+            if let Some(hunk) = parse_hunk_header(line) {
+                file.hunks.push(hunk);
+                continue;
+            }
+        }
+
+        let hunk = match file.hunks.last_mut() {
+            Some(hunk) => hunk,
+            None => {
+                if let Some(path) = line.strip_prefix("--- ") {
+                    file.old_path = strip_path(path, "a/");
+                } else if let Some(path) = line.strip_prefix("+++ ") {
+                    file.new_path = strip_path(path, "b/");
+                }
+                file.header.push(line.to_string());
+                continue;
+            }
+        };
+
+        let kind = match line.chars().next() {
+            Some('+') => LineKind::Added,
+            Some('-') => LineKind::Removed,
+            Some(' ') | None => LineKind::Context,
+            Some('\\') => {
+                if let Some(last) = hunk.lines.last_mut() {
+                    last.no_newline = true;
+                }
+                continue;
+            }
+            Some(_) => continue,
+        };
+        hunk.lines.push(Line {
+            kind,
+            text: line.get(1..).unwrap_or("").to_string(),
+            no_newline: false,
+        });
+    }
+
+    // Files without `---`/`+++` lines, e.g. binary ones, only name them in `diff --git`.
+    for file in &mut files {
+        if file.old_path.is_none() && file.new_path.is_none() {
+            let paths = file.header[0].strip_prefix("diff --git a/");
+            if let Some((old, new)) = paths.and_then(|paths| paths.split_once(" b/")) {
+                file.old_path = Some(old.to_string());
+                file.new_path = Some(new.to_string());
+            }
+        }
+    }
+
+    files
+}
+
+/// Parses `@@ -1,5 +1,6 @@ fn main() {`. A missing length means 1.
+fn parse_hunk_header(line: &str) -> Option<Hunk> {
+    let rest = line.strip_prefix("@@ -")?;
+    let (ranges, section) = rest.split_once(" @@")?;
+    let (old, new) = ranges.split_once(" +")?;
+
+    let range = |range: &str| -> Option<(usize, usize)> {
+        match range.split_once(',') {
+            Some((start, len)) => Some((start.parse().ok()?, len.parse().ok()?)),
+            None => Some((range.parse().ok()?, 1)),
+        }
+    };
+    let (old_start, old_len) = range(old)?;
+    let (new_start, new_len) = range(new)?;
+
+    Some(Hunk {
+        old_start,
+        old_len,
+        new_start,
+        new_len,
+        section: section.trim_start().to_string(),
+        lines: vec![],
+    })
+}
+
+fn strip_path(path: &str, prefix: &str) -> Option<String> {
+    let path = path.trim_end_matches('\t');
+    if path == "/dev/null" {
+        None
+    } else {
+        Some(path.strip_prefix(prefix).unwrap_or(path).to_string())
+    }
+}
+
+impl FileDiff {
+    /// The path of the file, preferring the new one.
+    pub fn path(&self) -> &str {
+        self.new_path
+            .as_deref()
+            .or(self.old_path.as_deref())
+            .unwrap_or("")
+    }
+
+    /// A patch with only the selected changes, which applies to the old version of the
+    /// file, e.g. with `git apply --cached`. `selected` has a flag per line of each hunk,
+    /// and those of context lines are ignored. Unselected removals become context, and
+    /// unselected additions are left out. Returns `None` if nothing is selected.
+    pub fn patch(&self, selected: &[Vec<bool>]) -> Option<String> {
+        let is_selected =
+            |h: usize, l: usize| selected.get(h).and_then(|s| s.get(l)) == Some(&true);
+
+        let mut all = true;
+        let mut any = false;
+        for (h, hunk) in self.hunks.iter().enumerate() {
+            for (l, line) in hunk.lines.iter().enumerate() {
+                if line.is_change() {
+                    any |= is_selected(h, l);
+                    all &= is_selected(h, l);
+                }
+            }
+        }
+        if !any {
+            return None;
+        }
+
+        let mut patch = String::new();
+        for line in &self.header {
+            // A deletion is only a deletion if every line is removed.
+            if !all && line.starts_with("deleted file mode") {
+                continue;
+            }
+            if !all && self.new_path.is_none() && line.starts_with("+++ ") {
+                writeln!(patch, "+++ b/{}", self.path()).unwrap();
+                continue;
+            }
+            writeln!(patch, "{}", line).unwrap();
+        }
+
+        // How far the new side has drifted from the old, after the hunks so far.
+        let mut offset = 0_isize;
+        for (h, hunk) in self.hunks.iter().enumerate() {
+            let mut lines = vec![];
+            for (l, line) in hunk.lines.iter().enumerate() {
+                match (line.kind, is_selected(h, l)) {
+                    (LineKind::Context, _)
+                    | (LineKind::Added, true)
+                    | (LineKind::Removed, true) => lines.push((line.kind, line)),
+                    (LineKind::Removed, false) => lines.push((LineKind::Context, line)),
+                    (LineKind::Added, false) => {}
+                }
+            }
+            if lines.iter().all(|(kind, _)| *kind == LineKind::Context) {
+                continue;
+            }
+
+            let old_len = lines
+                .iter()
+                .filter(|(kind, _)| *kind != LineKind::Added)
+                .count();
+            let new_len = lines
+                .iter()
+                .filter(|(kind, _)| *kind != LineKind::Removed)
+                .count();
+
+            // An empty range starts at the line before it.
+            let first = if hunk.old_len == 0 {
+                hunk.old_start + 1
+            } else {
+                hunk.old_start
+            };
+            let new_first = (first as isize + offset) as usize;
+            let new_start = if new_len == 0 {
+                new_first - 1
+            } else {
+                new_first
+            };
+            offset += new_len as isize - old_len as isize;
+
+            writeln!(
+                patch,
+                "@@ -{},{} +{},{} @@{}",
+                hunk.old_start,
+                old_len,
+                new_start,
+                new_len,
+                if hunk.section.is_empty() {
+                    String::new()
+                } else {
+                    format!(" {}", hunk.section)
+                }
+            )
+            .unwrap();
+            for (kind, line) in lines {
+                writeln!(patch, "{}{}", kind.prefix(), line.text).unwrap();
+                if line.no_newline {
+                    patch.push_str("\\ No newline at end of file\n");
+                }
+            }
+        }
+
+        Some(patch)
+    }
+}
+
+impl Hunk {
+    /// The byte ranges of each line's text that differ from the line it replaces, for
+    /// highlighting. A run of removed lines followed by added lines is paired up in order.
+    /// Lines without a counterpart, or with nothing in common with it, have no ranges.
+    pub fn changed_words(&self) -> Vec<Ranges> {
+        let mut ranges = vec![vec![]; self.lines.len()];
+
+        let mut i = 0;
+        while i < self.lines.len() {
+            let removed = i;
+            while i < self.lines.len() && self.lines[i].kind == LineKind::Removed {
+                i += 1;
+            }
+            let added = i;
+            while i < self.lines.len() && self.lines[i].kind == LineKind::Added {
+                i += 1;
+            }
+
+            let pairs = (added - removed).min(i - added);
+            for n in 0..pairs {
+                let (old, new) = (removed + n, added + n);
+                if let Some((old_ranges, new_ranges)) =
+                    word_diff(&self.lines[old].text, &self.lines[new].text)
+                {
+                    ranges[old] = old_ranges;
+                    ranges[new] = new_ranges;
+                }
+            }
+
+            if i == removed {
+                i += 1;
+            }
+        }
+
+        ranges
+    }
+}
+
+/// Splits a line into words, runs of whitespace, and single other chars, as byte ranges.
+fn tokens(text: &str) -> Ranges {
+    let class = |c: char| {
+        if c.is_alphanumeric() || c == '_' {
+            0
+        } else if c.is_whitespace() {
+            1
+        } else {
+            2
+        }
+    };
+
+    let mut tokens: Vec<Range<usize>> = vec![];
+    let mut prev = None;
+    for (i, c) in text.char_indices() {
+        let class = class(c);
+        match tokens.last_mut() {
+            Some(last) if prev == Some(class) && class != 2 => last.end = i + c.len_utf8(),
+            _ => tokens.push(i..i + c.len_utf8()),
+        }
+        prev = Some(class);
+    }
+
+    tokens
+}
+
+/// Byte ranges of a line's text.
+type Ranges = Vec<Range<usize>>;
+
+/// Above this many token pairs, lines aren't compared word by word.
+const MAX_WORD_DIFF: usize = 40_000;
+
+/// The byte ranges of the tokens in `old` and `new` that aren't in their longest common
+/// subsequence, with adjacent ranges merged. `None` if the lines have nothing in common,
+/// or are too long to compare.
+fn word_diff(old: &str, new: &str) -> Option<(Ranges, Ranges)> {
+    let (a, b) = (tokens(old), tokens(new));
+    if a.len() * b.len() > MAX_WORD_DIFF {
+        return None;
+    }
+
+    // lengths[i][j] is the LCS length of a[i..] and b[j..].
+    let mut lengths = vec![vec![0_usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lengths[i][j] = if old[a[i].clone()] == new[b[j].clone()] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let (mut old_ranges, mut new_ranges) = (vec![], vec![]);
+    let push = |ranges: &mut Vec<Range<usize>>, range: Range<usize>| match ranges.last_mut() {
+        Some(last) if last.end == range.start => last.end = range.end,
+        _ => ranges.push(range),
+    };
+
+    let mut common_text = false;
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && old[a[i].clone()] == new[b[j].clone()] {
+            common_text |= !old[a[i].clone()].trim().is_empty();
+            i += 1;
+            j += 1;
+        } else if j < b.len() && (i == a.len() || lengths[i][j + 1] >= lengths[i + 1][j]) {
+            push(&mut new_ranges, b[j].clone());
+            j += 1;
+        } else {
+            push(&mut old_ranges, a[i].clone());
+            i += 1;
+        }
+    }
+
+    // Only whitespace in common isn't worth highlighting.
+    if !common_text {
+        return None;
+    }
+
+    Some((old_ranges, new_ranges))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const DIFF: &str = "\
+diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,3 +1,3 @@ mod a;
+ one
+-two
++TWO
+ three
+@@ -10,2 +10,3 @@
+ ten
++ten and a half
+ eleven
+diff --git a/new.txt b/new.txt
+new file mode 100644
+index 0000000..3333333
+--- /dev/null
++++ b/new.txt
+@@ -0,0 +1 @@
++hello
+\\ No newline at end of file
+";
+
+    #[test]
+    fn parses_files_and_hunks() {
+        let files = parse(DIFF);
+        assert_eq!(files.len(), 2);
+
+        let lib = &files[0];
+        assert_eq!(lib.path(), "src/lib.rs");
+        assert_eq!(lib.header.len(), 4);
+        assert_eq!(lib.hunks.len(), 2);
+        assert_eq!(lib.hunks[0].section, "mod a;");
+        assert_eq!(
+            (
+                lib.hunks[1].old_start,
+                lib.hunks[1].old_len,
+                lib.hunks[1].new_len
+            ),
+            (10, 2, 3)
+        );
+        assert_eq!(lib.hunks[0].lines[1].kind, LineKind::Removed);
+        assert_eq!(lib.hunks[0].lines[2].text, "TWO");
+
+        let new = &files[1];
+        assert_eq!(new.old_path, None);
+        assert_eq!(new.path(), "new.txt");
+        assert_eq!((new.hunks[0].old_start, new.hunks[0].old_len), (0, 0));
+        assert!(new.hunks[0].lines[0].no_newline);
+    }
+
+    #[test]
+    fn patches_selected_lines() {
+        let lib = &parse(DIFF)[0];
+
+        assert_eq!(lib.patch(&[vec![false; 4], vec![false; 3]]), None);
+
+        // Only the second hunk.
+        let patch = lib.patch(&[vec![false; 4], vec![true; 3]]).unwrap();
+        assert!(patch.ends_with("@@ -10,2 +10,3 @@\n ten\n+ten and a half\n eleven\n"));
+        assert!(!patch.contains("TWO"));
+
+        // The removal without the addition, which shifts the later hunk up.
+        let patch = lib
+            .patch(&[vec![false, true, false, false], vec![true; 3]])
+            .unwrap();
+        assert!(patch.contains("@@ -1,3 +1,2 @@ mod a;\n one\n-two\n three\n"));
+        assert!(patch.contains("@@ -10,2 +9,3 @@\n"));
+
+        // The addition without the removal.
+        let patch = lib
+            .patch(&[vec![false, false, true, false], vec![false; 3]])
+            .unwrap();
+        assert!(patch.contains("@@ -1,3 +1,4 @@ mod a;\n one\n two\n+TWO\n three\n"));
+    }
+
+    #[test]
+    fn changed_words() {
+        let hunk = &parse(
+            "\
+diff --git a/a.rs b/a.rs
+--- a/a.rs
++++ b/a.rs
+@@ -1,4 +1,4 @@
+-let value = old_name(1);
+-removed entirely
++let value = new_name(1, 2);
++completely different
+ context
+",
+        )[0]
+        .hunks[0];
+
+        let words = hunk.changed_words();
+        let text = |line: usize| -> Vec<&str> {
+            words[line]
+                .iter()
+                .map(|range| &hunk.lines[line].text[range.clone()])
+                .collect()
+        };
+        assert_eq!(text(0), vec!["old_name"]);
+        assert_eq!(text(2), vec!["new_name", ", 2"]);
+
+        // Nothing but whitespace in common.
+        assert!(words[1].is_empty() && words[3].is_empty());
+        assert!(words[4].is_empty());
+    }
+
+    #[test]
+    fn patches_new_and_deleted_files() {
+        let new = &parse(DIFF)[1];
+        let patch = new.patch(&[vec![true]]).unwrap();
+        assert!(patch.contains("new file mode 100644\n"));
+        assert!(patch.ends_with("@@ -0,0 +1,1 @@\n+hello\n\\ No newline at end of file\n"));
+
+        let deleted = &parse(
+            "\
+diff --git a/old.txt b/old.txt
+deleted file mode 100644
+index 4444444..0000000
+--- a/old.txt
++++ /dev/null
+@@ -1,2 +0,0 @@
+-a
+-b
+",
+        )[0];
+        assert_eq!(deleted.new_path, None);
+        assert!(deleted
+            .patch(&[vec![true, true]])
+            .unwrap()
+            .contains("deleted file mode"));
+
+        // Removing some of the lines keeps the file.
+        let patch = deleted.patch(&[vec![true, false]]).unwrap();
+        assert!(!patch.contains("deleted file mode"));
+        assert!(patch.contains("+++ b/old.txt\n@@ -1,2 +1,1 @@\n-a\n b\n"));
+    }
+}
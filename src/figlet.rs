@@ -74,12 +74,8 @@ impl Figlet {
 
     pub fn from_file(path: impl AsRef<Path>) -> Result<Self, io::Error> {
         let contents = read_to_string(path)?;
-        parse(contents.lines()).ok_or_else(|| {
-            io::Error::new(
-                io::ErrorKind::Other,
-                "file exists but appears to be invalid",
-            )
-        })
+        parse(contents.lines())
+            .ok_or_else(|| io::Error::other("file exists but appears to be invalid"))
     }
 
     fn from_default() -> Self {
@@ -106,7 +102,7 @@ pub fn parse<'a>(mut iter: impl Iterator<Item = &'a str>) -> Option<Figlet> {
     let header: Vec<_> = iter.next()?.split(' ').collect();
 
     let height: usize = header.get(1)?.parse().ok()?;
-    let hard_blank = header.get(0)?.chars().last()?;
+    let hard_blank = header.first()?.chars().last()?;
     let comments: usize = header.get(5)?.parse().ok()?;
 
     let mut chars: Vec<Char> = Vec::new();
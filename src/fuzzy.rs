@@ -0,0 +1,119 @@
+//! Subsequence matching for filtering lists as the user types.
+
+/// Where a pattern matched, and how well.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FuzzyMatch {
+    /// Higher is better. Scattered matches in long text can be negative.
+    pub score: i64,
+    /// The char indices of `text` that matched, in order.
+    pub indices: Vec<usize>,
+}
+
+const MATCH: i64 = 16;
+const CONSECUTIVE: i64 = 16;
+const WORD_START: i64 = 12;
+const FIRST_CHAR: i64 = 8;
+const GAP: i64 = 3;
+/// Per char after the last match, so that shorter texts win ties.
+const TRAILING: i64 = 1;
+
+/// Matches the chars of `pattern` in order, ignoring case, preferring consecutive chars
+/// and the starts of words. An empty pattern matches everything.
+pub fn fuzzy_match(pattern: &str, text: &str) -> Option<FuzzyMatch> {
+    let pattern: Vec<char> = pattern.chars().flat_map(char::to_lowercase).collect();
+    let chars: Vec<char> = text.chars().collect();
+    let lower: Vec<char> = chars
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+
+    let first = match pattern.first() {
+        Some(first) => *first,
+        None => {
+            return Some(FuzzyMatch {
+                score: 0,
+                indices: vec![],
+            })
+        }
+    };
+
+    // Greedy matching from each occurrence of the first char, keeping the best. If it
+    // fails from one start, it fails from every later one too.
+    let mut best: Option<FuzzyMatch> = None;
+    for start in (0..lower.len()).filter(|&i| lower[i] == first) {
+        let mut indices = vec![start];
+        for c in &pattern[1..] {
+            let from = indices[indices.len() - 1] + 1;
+            match lower[from..].iter().position(|l| l == c) {
+                Some(offset) => indices.push(from + offset),
+                None => return best,
+            }
+        }
+
+        let score = score(&chars, &indices);
+        if best.as_ref().is_none_or(|best| score > best.score) {
+            best = Some(FuzzyMatch { score, indices });
+        }
+    }
+
+    best
+}
+
+fn score(chars: &[char], indices: &[usize]) -> i64 {
+    let mut score = 0;
+    let mut prev: Option<usize> = None;
+
+    for &i in indices {
+        score += MATCH;
+
+        if i == 0 {
+            score += FIRST_CHAR;
+        }
+        if i == 0 || !chars[i - 1].is_alphanumeric() {
+            score += WORD_START;
+        }
+
+        match prev {
+            Some(prev) if i == prev + 1 => score += CONSECUTIVE,
+            Some(prev) => score -= GAP * (i - prev - 1) as i64,
+            None => score -= GAP * i as i64,
+        }
+        prev = Some(i);
+    }
+
+    if let Some(last) = prev {
+        score -= TRAILING * (chars.len() - last - 1) as i64;
+    }
+
+    score
+}
+
+#[cfg(test)]
+mod test {
+    use super::fuzzy_match;
+
+    fn indices(pattern: &str, text: &str) -> Option<Vec<usize>> {
+        fuzzy_match(pattern, text).map(|m| m.indices)
+    }
+
+    #[test]
+    fn subsequences() {
+        assert_eq!(indices("", "feat"), Some(vec![]));
+        assert_eq!(indices("ft", "feat"), Some(vec![0, 3]));
+        assert_eq!(indices("RF", "refactor"), Some(vec![0, 2]));
+        assert_eq!(indices("tf", "feat"), None);
+
+        // The later, consecutive match scores better than the first occurrence.
+        assert_eq!(indices("fix", "a fine fix"), Some(vec![7, 8, 9]));
+    }
+
+    #[test]
+    fn ranking() {
+        let score = |pattern, text| fuzzy_match(pattern, text).unwrap().score;
+
+        assert!(score("fa", "feat") > score("fa", "refactor"));
+        assert!(score("perf", "perf") > score("perf", "performance"));
+        assert!(score("bug", "A bug fix") > 0);
+        assert!(score("bs", "Changes to the build system or dependencies") < 0);
+    }
+}
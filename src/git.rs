@@ -1,13 +1,12 @@
-use std::env::current_dir;
+use crate::version::Version;
+use std::env::{self, current_dir};
 use std::ffi::OsStr;
 use std::fmt;
-use std::io::Cursor;
-use std::io::Read;
+use std::io::Write;
 use std::io::{self, BufRead, BufReader};
 use std::path::Path;
 use std::path::PathBuf;
 use std::process::{Command, Stdio};
-use std::thread::spawn;
 
 mod parse_log;
 
@@ -20,25 +19,59 @@ pub struct Git {
 }
 
 #[derive(Debug, Clone)]
-pub struct GitStatus(pub Vec<GitStatusItem>);
+pub struct GitStatus {
+    items: Vec<GitStatusItem>,
+    /// The paths that aren't valid UTF-8, lossily converted. They're left out of `items`.
+    skipped: Vec<String>,
+}
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub struct GitStatusItem {
     file_name: String,
+    /// The path the file was renamed or copied from.
+    orig_name: Option<String>,
     staged: Option<GitStatusType>,
     unstaged: Option<GitStatusType>,
+    conflict: Option<Conflict>,
+    submodule: Option<SubmoduleState>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum GitStatusType {
     Added,
     Modified,
+    TypeChanged,
     Renamed,
+    Copied,
     Untracked,
     Deleted,
+    Unmerged,
     None,
 }
 
+/// How each side of a merge changed an unmerged file.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Conflict {
+    BothAdded,
+    BothModified,
+    BothDeleted,
+    AddedByUs,
+    AddedByThem,
+    DeletedByUs,
+    DeletedByThem,
+}
+
+/// What changed in a submodule's working tree.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct SubmoduleState {
+    /// It's checked out at a different commit than the index records.
+    pub commit_changed: bool,
+    /// Its tracked files have changes.
+    pub modified: bool,
+    /// It has untracked files.
+    pub untracked: bool,
+}
+
 #[derive(Debug)]
 pub enum GitError {
     NotGitRepo,
@@ -65,6 +98,151 @@ impl Git {
         }
     }
 
+    pub fn repo_root(&self) -> &Path {
+        &self.repo_root
+    }
+
+    /// The directory git runs hooks from, respecting `core.hooksPath`.
+    pub fn hooks_dir(&self) -> io::Result<PathBuf> {
+        let output = Command::new("git")
+            .current_dir(&self.repo_root)
+            .arg("rev-parse")
+            .arg("--git-path")
+            .arg("hooks")
+            .stderr(Stdio::inherit())
+            .output()?;
+
+        if !output.status.success() {
+            return Err(io::Error::other("git rev-parse --git-path hooks failed"));
+        }
+
+        let path = String::from_utf8_lossy(&output.stdout);
+        Ok(self.repo_root.join(path.trim()))
+    }
+
+    /// The newest version tag reachable from HEAD, e.g. `("v1.2.3", 1.2.3)`. Releases are
+    /// counted from it, by both `glint changelog` and `glint bump`.
+    pub fn last_version_tag(&self) -> io::Result<Option<(String, Version)>> {
+        Ok(self
+            .merged_tags()?
+            .into_iter()
+            .filter_map(|tag| {
+                let version = tag.parse::<Version>().ok()?;
+                Some((tag, version))
+            })
+            .max_by(|(_, a), (_, b)| a.cmp(b)))
+    }
+
+    /// The tags reachable from HEAD.
+    pub fn merged_tags(&self) -> io::Result<Vec<String>> {
+        let output = Command::new("git")
+            .current_dir(&self.repo_root)
+            .arg("tag")
+            .arg("--merged")
+            .arg("HEAD")
+            .stderr(Stdio::inherit())
+            .output()?;
+
+        if !output.status.success() {
+            return Err(io::Error::other("git tag --merged HEAD failed"));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(String::from)
+            .collect())
+    }
+
+    /// Creates an annotated tag at HEAD.
+    pub fn tag(&self, name: &str, message: &str) -> Command {
+        let mut command = Command::new("git");
+
+        command.current_dir(&self.repo_root);
+        command.stdin(Stdio::null());
+
+        command.arg("tag");
+        command.arg("--annotate");
+        command.arg("--message");
+        command.arg(message);
+        command.arg(name);
+
+        command
+    }
+
+    /// The fetch URL of a remote, e.g. `origin`, if it exists.
+    pub fn remote_url(&self, remote: &str) -> io::Result<Option<String>> {
+        let output = Command::new("git")
+            .current_dir(&self.repo_root)
+            .arg("remote")
+            .arg("get-url")
+            .arg(remote)
+            .stderr(Stdio::null())
+            .output()?;
+
+        if !output.status.success() {
+            return Ok(None);
+        }
+
+        Ok(Some(
+            String::from_utf8_lossy(&output.stdout).trim().to_string(),
+        ))
+    }
+
+    /// The remote-tracking branches that contain `rev`, e.g. `origin/main`.
+    pub fn remote_branches_containing(&self, rev: &str) -> io::Result<Vec<String>> {
+        let output = Command::new("git")
+            .current_dir(&self.repo_root)
+            .arg("branch")
+            .arg("--remotes")
+            .arg("--contains")
+            .arg(rev)
+            .arg("--format=%(refname:short)")
+            .stderr(Stdio::inherit())
+            .output()?;
+
+        if !output.status.success() {
+            return Err(io::Error::other(format!(
+                "git branch --remotes --contains {} failed",
+                rev
+            )));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(String::from)
+            .collect())
+    }
+
+    /// Rebases the commits after `commit`'s parent with `--autosquash`, accepting the todo
+    /// list without opening an editor.
+    pub fn autosquash(&self, commit: &str) -> io::Result<Command> {
+        let has_parent = Command::new("git")
+            .current_dir(&self.repo_root)
+            .arg("rev-parse")
+            .arg("--verify")
+            .arg("--quiet")
+            .arg(format!("{}^", commit))
+            .stdout(Stdio::null())
+            .status()?
+            .success();
+
+        let mut command = Command::new("git");
+
+        command.current_dir(&self.repo_root);
+        command.env("GIT_SEQUENCE_EDITOR", "true");
+
+        command.arg("rebase");
+        command.arg("--interactive");
+        command.arg("--autosquash");
+        if has_parent {
+            command.arg(format!("{}^", commit));
+        } else {
+            command.arg("--root");
+        }
+
+        Ok(command)
+    }
+
     pub fn commit<I>(&self, message: &str, other_args: impl IntoIterator<Item = I>) -> Command
     where
         I: AsRef<OsStr>,
@@ -111,11 +289,25 @@ impl Git {
     where
         I: AsRef<OsStr>,
     {
-        let proc = self.log(other_args).stdout(Stdio::piped()).spawn()?;
-        let stdout = proc.stdout.expect("must be able to access stdout");
-        Ok(parse_log::parse_logs(
-            BufReader::new(stdout).lines().filter_map(Result::ok),
-        ))
+        let mut proc = self.log(other_args).stdout(Stdio::piped()).spawn()?;
+        let stdout = proc.stdout.take().expect("must be able to access stdout");
+        // Lines are decoded lossily, so that a message that isn't UTF-8 doesn't stop the
+        // reading and leave git writing to a closed pipe.
+        let lines = BufReader::new(stdout)
+            .split(b'\n')
+            .map_while(Result::ok)
+            .map(|line| {
+                let line = line.strip_suffix(b"\r").unwrap_or(&line);
+                String::from_utf8_lossy(line).into_owned()
+            });
+        let logs = parse_log::parse_logs(lines);
+
+        let status = proc.wait()?;
+        if !status.success() {
+            return Err(io::Error::other(format!("git log failed with {}", status)));
+        }
+
+        Ok(logs)
     }
 
     /// Stages files using `git add`. Run from the repo root.gs
@@ -140,80 +332,203 @@ impl Git {
         command
     }
 
-    pub fn less(&self, file: impl AsRef<OsStr>) -> io::Result<()> {
-        Command::new("less")
-            .arg(file.as_ref())
-            .current_dir(&self.repo_root)
-            .status()?;
+    /// Unstages files, keeping their changes in the working tree.
+    pub fn restore_staged<I>(&self, files: impl IntoIterator<Item = I>) -> Command
+    where
+        I: AsRef<OsStr>,
+    {
+        let mut command = Command::new("git");
 
-        Ok(())
+        command.current_dir(&self.repo_root);
+        command.stdin(Stdio::null());
+
+        command.arg("restore");
+        command.arg("--staged");
+        command.arg("--");
+
+        for file in files {
+            command.arg(file.as_ref());
+        }
+
+        command
     }
 
-    /// Prints the
-    pub fn directory_untracked_less(&self, dir: &Path) -> io::Result<()> {
-        let ls = Command::new("git")
-            .current_dir(&self.repo_root)
-            .arg("ls-files")
-            .arg("--others")
-            .arg("--exclude-standard")
-            .arg("--")
-            .arg(dir)
-            .stdout(Stdio::piped())
-            .spawn()?;
+    /// The unstaged changes to a file, as a unified diff. An untracked file is compared
+    /// to an empty one. With `color`, it has git's colors, for showing it in a pager.
+    pub fn diff_file(&self, file: &str, untracked: bool, color: bool) -> io::Result<String> {
+        let mut command = Command::new("git");
 
-        let ls_stdout = ls.stdout.ok_or_else(|| {
-            io::Error::new(io::ErrorKind::Other, "failed to get stdout of git diff")
-        })?;
+        command.current_dir(&self.repo_root);
+        command.stdin(Stdio::null());
+        command.stderr(Stdio::inherit());
+
+        command.arg("diff");
+        command.arg(color_arg(color));
+        command.arg("--no-ext-diff");
+        if untracked {
+            command.arg("--no-index");
+            command.arg("--");
+            command.arg("/dev/null");
+        } else {
+            command.arg("--");
+        }
+        command.arg(file);
+
+        let output = command.output()?;
+
+        // With --no-index, the status is 1 when the files differ.
+        let failed = match output.status.code() {
+            Some(0) => false,
+            Some(1) => !untracked,
+            _ => true,
+        };
+        if failed {
+            return Err(io::Error::other(format!("git diff {} failed", file)));
+        }
 
-        let message = format!("= Contents of {} =", dir.to_string_lossy());
-        let prefix = format!(
-            "{bar}\n{message}\n{bar}\n\n",
-            message = message,
-            bar = "=".repeat(message.len())
-        );
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
 
-        let mut less = Command::new("less")
-            .arg("-R")
-            .current_dir(&self.repo_root)
-            .stdin(Stdio::piped())
-            .spawn()?;
+    /// Stages a patch read from stdin, e.g. one from `FileDiff::patch`.
+    pub fn apply_cached(&self) -> Command {
+        let mut command = Command::new("git");
 
-        let mut stdin = less.stdin.take().expect("Failed to open stdin");
-        spawn(move || {
-            let suffix = "\n= End =\n";
-            let prefix = Cursor::new(prefix);
+        command.current_dir(&self.repo_root);
+        command.stdin(Stdio::piped());
 
-            let mut input = prefix.chain(ls_stdout).chain(Cursor::new(suffix));
+        command.arg("apply");
+        command.arg("--cached");
+        command.arg("--recount");
+        command.arg("-");
 
-            let _r = io::copy(&mut input, &mut stdin);
-        });
+        command
+    }
 
-        less.wait()?;
+    /// The unstaged changes to tracked files, as a unified diff. All of them when `files`
+    /// is empty. With `color`, it has git's colors.
+    pub fn diff<I>(&self, files: impl IntoIterator<Item = I>, color: bool) -> io::Result<String>
+    where
+        I: AsRef<OsStr>,
+    {
+        self.run_diff(None, files, color)
+    }
 
-        Ok(())
+    /// The staged changes to files, like `diff` gives the unstaged ones.
+    pub fn diff_cached<I>(
+        &self,
+        files: impl IntoIterator<Item = I>,
+        color: bool,
+    ) -> io::Result<String>
+    where
+        I: AsRef<OsStr>,
+    {
+        self.run_diff(Some("--cached"), files, color)
     }
 
-    pub fn diff_less<I>(&self, files: impl IntoIterator<Item = I>) -> io::Result<()>
+    fn run_diff<I>(
+        &self,
+        option: Option<&str>,
+        files: impl IntoIterator<Item = I>,
+        color: bool,
+    ) -> io::Result<String>
     where
         I: AsRef<OsStr>,
     {
-        let diff = Command::new("git")
+        let output = Command::new("git")
             .current_dir(&self.repo_root)
             .arg("diff")
-            .arg("--color=always")
+            .args(option)
+            .arg(color_arg(color))
+            .arg("--no-ext-diff")
             .arg("--")
-            .args(files.into_iter())
-            .stdout(Stdio::piped())
-            .spawn()?;
+            .args(files)
+            .stdin(Stdio::null())
+            .stderr(Stdio::inherit())
+            .output()?;
+
+        if !output.status.success() {
+            return Err(io::Error::other(format!(
+                "git diff failed with {}",
+                output.status
+            )));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// The untracked files in a directory, leaving out ignored ones.
+    pub fn untracked_files(&self, dir: &str) -> io::Result<Vec<String>> {
+        let output = Command::new("git")
+            .current_dir(&self.repo_root)
+            .arg("ls-files")
+            .arg("--others")
+            .arg("--exclude-standard")
+            .arg("--")
+            .arg(dir)
+            .stderr(Stdio::inherit())
+            .output()?;
+
+        if !output.status.success() {
+            return Err(io::Error::other(format!(
+                "git ls-files failed with {}",
+                output.status
+            )));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(String::from)
+            .collect())
+    }
+
+    /// The pager git would use: `$GIT_PAGER`, `core.pager` or `$PAGER`, falling back to
+    /// `less`.
+    pub fn pager(&self) -> String {
+        let var = |name| env::var(name).ok().filter(|pager| !pager.is_empty());
+        let core_pager = || {
+            let output = Command::new("git")
+                .current_dir(&self.repo_root)
+                .arg("config")
+                .arg("core.pager")
+                .stderr(Stdio::null())
+                .output()
+                .ok()?;
+            let pager = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            Some(pager).filter(|pager| output.status.success() && !pager.is_empty())
+        };
+
+        var("GIT_PAGER")
+            .or_else(core_pager)
+            .or_else(|| var("PAGER"))
+            .unwrap_or_else(|| "less".into())
+    }
 
-        Command::new("less")
-            .arg("-R")
+    /// Shows `text` with a pager command, which is run by the shell like git runs it.
+    pub fn page(&self, pager: &str, text: &str) -> io::Result<()> {
+        let mut command = if cfg!(windows) {
+            let mut command = Command::new("cmd");
+            command.arg("/C");
+            command
+        } else {
+            let mut command = Command::new("sh");
+            command.arg("-c");
+            command
+        };
+
+        let mut child = command
+            .arg(pager)
             .current_dir(&self.repo_root)
-            .stdin(diff.stdout.ok_or_else(|| {
-                io::Error::new(io::ErrorKind::Other, "failed to get stdout of git diff")
-            })?)
-            .status()?;
+            // The same defaults as git, so that less shows colors and quits on short input.
+            .env("LESS", env::var("LESS").unwrap_or_else(|_| "FRX".into()))
+            .stdin(Stdio::piped())
+            .spawn()?;
 
+        // The pager may quit before reading everything.
+        let mut stdin = child.stdin.take().expect("the pager's stdin is piped");
+        let _r = stdin.write_all(text.as_bytes());
+        drop(stdin);
+
+        child.wait()?;
         Ok(())
     }
 
@@ -226,47 +541,55 @@ impl Git {
 
         // Args
         command.arg("status");
-        command.arg("--porcelain");
-
-        let stdout = command.spawn()?.stdout.ok_or_else(|| {
-            io::Error::new(io::ErrorKind::Other, "Could not capture standard output.")
-        })?;
+        command.arg("--porcelain=v2");
+        command.arg("-z");
 
-        let items = BufReader::new(stdout)
-            .lines()
-            .filter_map(|line| line.ok())
-            .filter_map(|line| {
-                let mut chars = line.chars();
-                let staged = chars
-                    .next()
-                    .and_then(GitStatusType::from_char)
-                    .filter(|item| match item {
-                        GitStatusType::Untracked => false,
-                        _ => true,
-                    });
-                let unstaged = chars.next().and_then(GitStatusType::from_char);
-
-                chars.next();
-                let file: String = chars.collect();
-
-                if file.is_empty() {
-                    None
-                } else {
-                    Some(GitStatusItem {
-                        file_name: file,
-                        staged,
-                        unstaged,
-                    })
-                }
-            })
-            .collect();
+        let output = command.output()?;
+        if !output.status.success() {
+            return Err(io::Error::other("git status failed"));
+        }
 
-        Ok(GitStatus(items))
+        Ok(GitStatus::parse(&output.stdout))
     }
 }
 impl GitStatus {
+    /// Parses the output of `git status --porcelain=v2 -z`. Unknown records, such as
+    /// branch headers and ignored files, are skipped, and so are paths that aren't UTF-8,
+    /// since they can't be passed back to git as they are.
+    pub fn parse(output: &[u8]) -> Self {
+        let mut items = vec![];
+        let mut skipped = vec![];
+        let mut records = output.split(|&b| b == b'\0');
+
+        while let Some(record) = records.next() {
+            // A rename or copy is followed by its original path.
+            let orig_name = match record.starts_with(b"2 ") {
+                true => records.next(),
+                false => None,
+            };
+
+            match (
+                std::str::from_utf8(record),
+                orig_name.map(std::str::from_utf8).transpose(),
+            ) {
+                (Ok(record), Ok(orig_name)) => items.extend(parse_record(record, orig_name)),
+                _ => {
+                    let record = String::from_utf8_lossy(record);
+                    skipped.extend(parse_record(&record, None).map(|item| item.file_name));
+                }
+            }
+        }
+
+        GitStatus { items, skipped }
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = &GitStatusItem> {
-        self.0.iter()
+        self.items.iter()
+    }
+
+    /// The paths left out because they aren't valid UTF-8.
+    pub fn skipped(&self) -> &[String] {
+        &self.skipped
     }
 
     pub fn any_staged(&self) -> bool {
@@ -277,8 +600,20 @@ impl GitStatus {
         self.iter().any(|item| item.unstaged.is_some())
     }
 
+    /// The files that are staged for the next commit.
+    pub fn staged_files(&self) -> Vec<String> {
+        self.iter()
+            .filter(|item| item.staged.is_some())
+            .map(String::from)
+            .collect()
+    }
+
     pub fn len(&self) -> usize {
-        self.0.len()
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
     }
 }
 
@@ -286,17 +621,48 @@ impl GitStatusItem {
     pub fn new(file_name: String) -> Self {
         GitStatusItem {
             file_name,
+            orig_name: None,
             staged: None,
             unstaged: None,
+            conflict: None,
+            submodule: None,
         }
     }
     pub fn file_name(&self) -> &str {
         &self.file_name
     }
-    pub fn status(&self) -> &GitStatusType {
+
+    /// The path a renamed or copied file came from.
+    pub fn orig_name(&self) -> Option<&str> {
+        self.orig_name.as_deref()
+    }
+
+    pub fn conflict(&self) -> Option<Conflict> {
+        self.conflict
+    }
+
+    /// The state of a submodule, or None if this isn't one.
+    pub fn submodule(&self) -> Option<SubmoduleState> {
+        self.submodule
+    }
+    /// The change staged in the index.
+    pub fn index_status(&self) -> &GitStatusType {
+        self.staged.as_ref().unwrap_or(&GitStatusType::None)
+    }
+
+    /// The change in the working tree that isn't staged.
+    pub fn worktree_status(&self) -> &GitStatusType {
         self.unstaged.as_ref().unwrap_or(&GitStatusType::None)
     }
 
+    pub fn is_staged(&self) -> bool {
+        self.staged.is_some()
+    }
+
+    pub fn is_unstaged(&self) -> bool {
+        self.unstaged.is_some()
+    }
+
     pub fn is_dir(&self) -> bool {
         self.file_name.ends_with('/')
     }
@@ -306,15 +672,15 @@ impl GitStatusItem {
     }
 }
 
-impl Into<String> for GitStatusItem {
-    fn into(self) -> String {
-        (&self).into()
+impl From<GitStatusItem> for String {
+    fn from(item: GitStatusItem) -> String {
+        item.file_name
     }
 }
 
-impl Into<String> for &'_ GitStatusItem {
-    fn into(self) -> String {
-        self.file_name().into()
+impl From<&'_ GitStatusItem> for String {
+    fn from(item: &GitStatusItem) -> String {
+        item.file_name().into()
     }
 }
 
@@ -323,14 +689,105 @@ impl GitStatusType {
         match ch {
             'A' => Some(GitStatusType::Added),
             'M' => Some(GitStatusType::Modified),
+            'T' => Some(GitStatusType::TypeChanged),
             'R' => Some(GitStatusType::Renamed),
+            'C' => Some(GitStatusType::Copied),
             'D' => Some(GitStatusType::Deleted),
+            'U' => Some(GitStatusType::Unmerged),
             '?' => Some(GitStatusType::Untracked),
             _ => None,
         }
     }
 }
 
+impl Conflict {
+    /// Parses the `XY` field of an unmerged record.
+    fn from_xy(xy: &str) -> Option<Self> {
+        match xy {
+            "AA" => Some(Conflict::BothAdded),
+            "UU" => Some(Conflict::BothModified),
+            "DD" => Some(Conflict::BothDeleted),
+            "AU" => Some(Conflict::AddedByUs),
+            "UA" => Some(Conflict::AddedByThem),
+            "DU" => Some(Conflict::DeletedByUs),
+            "UD" => Some(Conflict::DeletedByThem),
+            _ => None,
+        }
+    }
+}
+
+impl SubmoduleState {
+    /// Parses the `<sub>` field, which is `N...` for anything but a submodule.
+    fn parse(field: &str) -> Option<Self> {
+        let mut chars = field.chars();
+        if chars.next() != Some('S') {
+            return None;
+        }
+
+        let mut flag = |set| chars.next() == Some(set);
+        Some(SubmoduleState {
+            commit_changed: flag('C'),
+            modified: flag('M'),
+            untracked: flag('U'),
+        })
+    }
+}
+
+/// The `git diff` argument for whether to color the diff. Colors are forced, since the
+/// output is captured rather than written to a terminal.
+fn color_arg(color: bool) -> &'static str {
+    match color {
+        true => "--color=always",
+        false => "--no-color",
+    }
+}
+
+/// Parses a record of any kind, along with the original path that follows a rename.
+fn parse_record(record: &str, orig_name: Option<&str>) -> Option<GitStatusItem> {
+    match record.split_at(record.len().min(2)) {
+        ("1 ", rest) => parse_changed(rest, 7),
+        ("2 ", rest) => parse_changed(rest, 8).map(|item| GitStatusItem {
+            orig_name: orig_name.map(String::from),
+            ..item
+        }),
+        ("u ", rest) => parse_unmerged(rest),
+        ("? ", path) => Some(GitStatusItem {
+            unstaged: Some(GitStatusType::Untracked),
+            ..GitStatusItem::new(path.to_string())
+        }),
+        _ => None,
+    }
+}
+
+/// Parses an ordinary (`1`) or renamed/copied (`2`) record after its type, where the path
+/// follows `fields` space-separated fields.
+fn parse_changed(record: &str, fields: usize) -> Option<GitStatusItem> {
+    let parts: Vec<&str> = record.splitn(fields + 1, ' ').collect();
+    let (xy, sub, path) = (parts.first()?, parts.get(1)?, parts.get(fields)?);
+
+    let mut xy = xy.chars();
+    Some(GitStatusItem {
+        staged: xy.next().and_then(GitStatusType::from_char),
+        unstaged: xy.next().and_then(GitStatusType::from_char),
+        submodule: SubmoduleState::parse(sub),
+        ..GitStatusItem::new(path.to_string())
+    })
+}
+
+/// Parses an unmerged (`u`) record after its type. Its changes count as unstaged, since
+/// adding the file marks it resolved.
+fn parse_unmerged(record: &str) -> Option<GitStatusItem> {
+    let parts: Vec<&str> = record.splitn(10, ' ').collect();
+    let (xy, sub, path) = (parts.first()?, parts.get(1)?, parts.get(9)?);
+
+    Some(GitStatusItem {
+        unstaged: Some(GitStatusType::Unmerged),
+        conflict: Conflict::from_xy(xy),
+        submodule: SubmoduleState::parse(sub),
+        ..GitStatusItem::new(path.to_string())
+    })
+}
+
 impl fmt::Display for GitError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -339,3 +796,143 @@ impl fmt::Display for GitError {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{Conflict, GitStatus, GitStatusItem, GitStatusType, SubmoduleState};
+
+    fn parse(records: &[&str]) -> Vec<GitStatusItem> {
+        GitStatus::parse(format!("{}\0", records.join("\0")).as_bytes()).items
+    }
+
+    const HASH: &str = "78981922613b2afb6025042ff6bd878ac1994e85";
+
+    #[test]
+    fn parses_ordinary_changes() {
+        let items = parse(&[
+            &format!("1 M. N... 100644 100644 100644 {0} {0} src/a b.rs", HASH),
+            &format!("1 .D N... 100644 100644 000000 {0} {0} gone.txt", HASH),
+            &format!("1 AT N... 000000 100644 120000 {0} {0} ü.txt", HASH),
+        ]);
+
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[0].file_name(), "src/a b.rs");
+        assert_eq!(items[0].index_status(), &GitStatusType::Modified);
+        assert_eq!(items[0].worktree_status(), &GitStatusType::None);
+        assert_eq!(items[1].index_status(), &GitStatusType::None);
+        assert_eq!(items[1].worktree_status(), &GitStatusType::Deleted);
+        assert_eq!(items[2].file_name(), "ü.txt");
+        assert_eq!(items[2].index_status(), &GitStatusType::Added);
+        assert_eq!(items[2].worktree_status(), &GitStatusType::TypeChanged);
+        assert!(items.iter().all(|item| item.orig_name().is_none()
+            && item.conflict().is_none()
+            && item.submodule().is_none()));
+    }
+
+    #[test]
+    fn parses_renames_and_copies() {
+        let items = parse(&[
+            &format!(
+                "2 R. N... 100644 100644 100644 {0} {0} R100 new name.txt",
+                HASH
+            ),
+            "old name.txt",
+            &format!("2 CM N... 100644 100644 100644 {0} {0} C75 copy.rs", HASH),
+            "orig.rs",
+            "? after.txt",
+        ]);
+
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[0].file_name(), "new name.txt");
+        assert_eq!(items[0].orig_name(), Some("old name.txt"));
+        assert_eq!(items[0].index_status(), &GitStatusType::Renamed);
+        assert_eq!(items[1].file_name(), "copy.rs");
+        assert_eq!(items[1].orig_name(), Some("orig.rs"));
+        assert_eq!(items[1].index_status(), &GitStatusType::Copied);
+        assert_eq!(items[1].worktree_status(), &GitStatusType::Modified);
+        assert_eq!(items[2].file_name(), "after.txt");
+    }
+
+    #[test]
+    fn parses_conflicts() {
+        let items = parse(&[
+            &format!(
+                "u UU N... 100644 100644 100644 100644 {0} {0} {0} both.txt",
+                HASH
+            ),
+            &format!(
+                "u DU N... 100644 000000 100644 100644 {0} {0} {0} deleted by us.txt",
+                HASH
+            ),
+        ]);
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].file_name(), "both.txt");
+        assert_eq!(items[0].conflict(), Some(Conflict::BothModified));
+        assert_eq!(items[0].worktree_status(), &GitStatusType::Unmerged);
+        assert!(!items[0].is_staged());
+        assert_eq!(items[1].file_name(), "deleted by us.txt");
+        assert_eq!(items[1].conflict(), Some(Conflict::DeletedByUs));
+    }
+
+    #[test]
+    fn parses_submodules() {
+        let items = parse(&[
+            &format!("1 .M S..U 160000 160000 160000 {0} {0} vendor/lib", HASH),
+            &format!("1 M. SC.. 160000 160000 160000 {0} {0} other", HASH),
+        ]);
+
+        assert_eq!(
+            items[0].submodule(),
+            Some(SubmoduleState {
+                untracked: true,
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            items[1].submodule(),
+            Some(SubmoduleState {
+                commit_changed: true,
+                ..Default::default()
+            })
+        );
+    }
+
+    #[test]
+    fn parses_untracked_and_skips_others() {
+        let items = parse(&[
+            "# branch.oid 0000000000000000000000000000000000000000",
+            "? new dir/",
+            "! ignored.log",
+            "? new.txt",
+        ]);
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].file_name(), "new dir/");
+        assert!(items[0].is_dir());
+        assert!(items[0].is_new());
+        assert_eq!(items[1].file_name(), "new.txt");
+        assert_eq!(items[1].worktree_status(), &GitStatusType::Untracked);
+        assert!(GitStatus::parse(b"").is_empty());
+    }
+
+    #[test]
+    fn skips_paths_that_are_not_utf8() {
+        let mut output = format!("1 .M N... 100644 100644 100644 {0} {0} bad", HASH).into_bytes();
+        output.extend(b"\xff.txt\0");
+        output.extend(
+            format!(
+                "2 R. N... 100644 100644 100644 {0} {0} R100 new.txt\0",
+                HASH
+            )
+            .bytes(),
+        );
+        output.extend(b"old\xfe.txt\0");
+        output.extend(b"? ok.txt\0");
+        let status = GitStatus::parse(&output);
+
+        assert_eq!(status.len(), 1);
+        assert_eq!(status.iter().next().unwrap().file_name(), "ok.txt");
+        assert_eq!(status.skipped(), ["bad\u{fffd}.txt", "new.txt"]);
+    }
+}
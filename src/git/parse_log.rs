@@ -1,3 +1,5 @@
+use crate::commitlint::{self, ConventionalCommit, ParseError};
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct LogItem {
     pub commit: String,
@@ -6,66 +8,11 @@ pub struct LogItem {
     pub files: Vec<String>,
 }
 
-#[derive(Debug, Clone, Eq, PartialEq)]
-pub struct Conventional<'a> {
-    pub ty: &'a str,
-    pub scope: Option<&'a str>,
-    pub message: &'a str,
-}
-
-fn bytes_until_non_ws(s: &str) -> usize {
-    let mut offset = 0;
-    for c in s.chars() {
-        if c.is_whitespace() {
-            offset += c.len_utf8();
-        } else {
-            break;
-        }
-    }
-    offset
-}
-
 impl LogItem {
-    /// Parse the message into the components (type, scope, message). Always returns
-    /// slices of the original message.
-    pub fn as_conventional(&self) -> Option<Conventional> {
-        let mut ty_pos = None;
-        let mut scope_pos = None;
-        let mut message_pos = None;
-
-        for (i, c) in self.message.char_indices() {
-            if ty_pos.is_none() {
-                if c == '(' || c == ':' {
-                    ty_pos = Some(0..i);
-
-                    if c == ':' {
-                        let start = i + 1 + bytes_until_non_ws(&self.message[i + 1..]);
-                        message_pos = Some(start..self.message.len());
-                        break;
-                    }
-                } else if !c.is_alphabetic() {
-                    return None;
-                }
-            } else if c == ':' {
-                let start = i + 1 + bytes_until_non_ws(&self.message[i + 1..]);
-
-                message_pos = Some(start..self.message.len());
-                break;
-            } else if c == ')' {
-                scope_pos = ty_pos.as_ref().map(|range| (range.end + 1)..i);
-            }
-        }
-
-        // println!("ty_pos: {:#?}\nscope: {:#?}\nmessage: {:#?}", ty_pos, scope_pos, message_pos);
-
-        match (ty_pos, scope_pos, message_pos) {
-            (Some(ty), scope, Some(message)) => Some(Conventional {
-                ty: &self.message[ty],
-                scope: scope.map(|scope| &self.message[scope]),
-                message: &self.message[message],
-            }),
-            _ => None,
-        }
+    /// Parse the message as a conventional commit. Always returns slices of the original
+    /// message.
+    pub fn as_conventional(&self) -> Result<ConventionalCommit<'_>, ParseError> {
+        commitlint::parse(&self.message)
     }
 }
 
@@ -98,6 +45,24 @@ impl Parser {
         std::mem::replace(self, Parser::Void)
     }
 
+    /// The item being parsed, if its message has started.
+    fn finish(self) -> Option<LogItem> {
+        match self {
+            Parser::MsgFooter {
+                commit,
+                epoch_secs,
+                message,
+                files,
+            } => Some(LogItem {
+                commit,
+                epoch_secs,
+                message: message.trim_end().into(),
+                files,
+            }),
+            _ => None,
+        }
+    }
+
     fn handle_line(&mut self, line: String) {
         use Parser::*;
 
@@ -226,6 +191,13 @@ pub fn parse_logs(lines: impl Iterator<Item = String>) -> Vec<LogItem> {
     let mut items = vec![];
 
     for line in lines {
+        // Commits without file changes (and the last commit) aren't followed by a blank
+        // line, so the next header also ends the current message.
+        if line.starts_with("commit ") {
+            items.extend(parser.take().finish());
+            parser = Parser::SeekingHeader;
+        }
+
         parser.handle_line(line);
 
         parser = match parser {
@@ -238,12 +210,13 @@ pub fn parse_logs(lines: impl Iterator<Item = String>) -> Vec<LogItem> {
         };
     }
 
+    items.extend(parser.finish());
     items
 }
 
 #[cfg(test)]
 mod test {
-    use super::{parse_logs, Conventional, LogItem};
+    use super::{parse_logs, LogItem};
     use std::io::{BufRead, BufReader};
 
     // Note: the whitespace here is important, and there is
@@ -270,9 +243,7 @@ committer Frankie Bagnardi <f.bagnardi@gmail.com> 1568585467 -0700
 
     #[test]
     fn parse_initial() {
-        let lines = BufReader::new(RAW.as_bytes())
-            .lines()
-            .filter_map(|r| r.ok());
+        let lines = BufReader::new(RAW.as_bytes()).lines().map_while(Result::ok);
         let logs = parse_logs(lines);
         assert_eq!(logs.len(), 1);
         assert_eq!(
@@ -286,19 +257,22 @@ committer Frankie Bagnardi <f.bagnardi@gmail.com> 1568585467 -0700
         );
     }
 
+    #[test]
+    fn parse_without_files() {
+        let raw = "commit aaaa\ncommitter A <a> 1 +0000\n\n    feat: one\n\ncommit bbbb\ncommitter A <a> 2 +0000\n\n    fix: two\n\n    body\n";
+        let logs = parse_logs(raw.lines().map(String::from));
+        let messages: Vec<_> = logs.iter().map(|log| log.message.as_str()).collect();
+        assert_eq!(messages, vec!["feat: one", "fix: two\n\nbody"]);
+    }
+
     #[test]
     fn as_conventional() {
-        let lines = BufReader::new(RAW.as_bytes())
-            .lines()
-            .filter_map(|r| r.ok());
+        let lines = BufReader::new(RAW.as_bytes()).lines().map_while(Result::ok);
         let logs = parse_logs(lines);
-        assert_eq!(
-            logs[0].as_conventional(),
-            Some(Conventional {
-                ty: "docs",
-                scope: Some("gif"),
-                message: "updates usage gif\n\nmuch better"
-            })
-        );
+        let conv = logs[0].as_conventional().unwrap();
+        assert_eq!(conv.ty, "docs");
+        assert_eq!(conv.scope, Some("gif"));
+        assert_eq!(conv.subject, "updates usage gif");
+        assert_eq!(conv.body, Some("much better"));
     }
 }
@@ -0,0 +1,179 @@
+//! Emoji prefixes for commit headers, in the style of [gitmoji].
+//!
+//! [gitmoji]: https://gitmoji.dev
+use serde::Deserialize;
+
+/// How a type's emoji is written at the start of the header.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GitmojiStyle {
+    /// `✨ feat: ...`
+    Emoji,
+    /// `:sparkles: feat: ...`
+    Shortcode,
+}
+
+/// Each gitmoji and its shortcode, without the colons.
+const GITMOJIS: &[(&str, &str)] = &[
+    ("🎨", "art"),
+    ("⚡️", "zap"),
+    ("🔥", "fire"),
+    ("🐛", "bug"),
+    ("🚑️", "ambulance"),
+    ("✨", "sparkles"),
+    ("📝", "memo"),
+    ("🚀", "rocket"),
+    ("💄", "lipstick"),
+    ("🎉", "tada"),
+    ("✅", "white_check_mark"),
+    ("🔒️", "lock"),
+    ("🔐", "closed_lock_with_key"),
+    ("🔖", "bookmark"),
+    ("🚨", "rotating_light"),
+    ("🚧", "construction"),
+    ("💚", "green_heart"),
+    ("⬇️", "arrow_down"),
+    ("⬆️", "arrow_up"),
+    ("📌", "pushpin"),
+    ("👷", "construction_worker"),
+    ("📈", "chart_with_upwards_trend"),
+    ("♻️", "recycle"),
+    ("➕", "heavy_plus_sign"),
+    ("➖", "heavy_minus_sign"),
+    ("🔧", "wrench"),
+    ("🔨", "hammer"),
+    ("🌐", "globe_with_meridians"),
+    ("✏️", "pencil2"),
+    ("💩", "poop"),
+    ("⏪️", "rewind"),
+    ("🔀", "twisted_rightwards_arrows"),
+    ("📦️", "package"),
+    ("👽️", "alien"),
+    ("🚚", "truck"),
+    ("📄", "page_facing_up"),
+    ("💥", "boom"),
+    ("🍱", "bento"),
+    ("♿️", "wheelchair"),
+    ("💡", "bulb"),
+    ("🍻", "beers"),
+    ("💬", "speech_balloon"),
+    ("🗃️", "card_file_box"),
+    ("🔊", "loud_sound"),
+    ("🔇", "mute"),
+    ("👥", "busts_in_silhouette"),
+    ("🚸", "children_crossing"),
+    ("🏗️", "building_construction"),
+    ("📱", "iphone"),
+    ("🤡", "clown_face"),
+    ("🥚", "egg"),
+    ("🙈", "see_no_evil"),
+    ("📸", "camera_flash"),
+    ("⚗️", "alembic"),
+    ("🔍️", "mag"),
+    ("🏷️", "label"),
+    ("🌱", "seedling"),
+    ("🚩", "triangular_flag_on_post"),
+    ("🥅", "goal_net"),
+    ("💫", "dizzy"),
+    ("🗑️", "wastebasket"),
+    ("🛂", "passport_control"),
+    ("🩹", "adhesive_bandage"),
+    ("🧐", "monocle_face"),
+    ("⚰️", "coffin"),
+    ("🧪", "test_tube"),
+    ("👔", "necktie"),
+    ("🩺", "stethoscope"),
+    ("🧱", "bricks"),
+    ("💸", "money_with_wings"),
+    ("🧵", "thread"),
+    ("🦺", "safety_vest"),
+    ("👌", "ok_hand"),
+];
+
+/// Emoji are compared without the variation selector, which is often left out.
+fn without_selector(emoji: &str) -> String {
+    emoji.replace('\u{fe0f}', "")
+}
+
+/// The `:shortcode:` for a gitmoji.
+pub fn shortcode(emoji: &str) -> Option<String> {
+    let emoji = without_selector(emoji);
+    GITMOJIS
+        .iter()
+        .find(|(e, _)| without_selector(e) == emoji)
+        .map(|(_, code)| format!(":{}:", code))
+}
+
+/// The gitmoji for a shortcode, with or without the colons.
+pub fn emoji(shortcode: &str) -> Option<&'static str> {
+    let code = shortcode.trim_matches(':');
+    GITMOJIS
+        .iter()
+        .find(|(_, c)| *c == code)
+        .map(|(emoji, _)| *emoji)
+}
+
+/// Writes `emoji`, which may be either an emoji or a `:shortcode:`, in `style`. Emoji
+/// that glint doesn't know are used as they are.
+pub fn format(emoji: &str, style: GitmojiStyle) -> String {
+    let is_shortcode = emoji.starts_with(':');
+    match style {
+        GitmojiStyle::Emoji if is_shortcode => self::emoji(emoji).unwrap_or(emoji).to_string(),
+        GitmojiStyle::Shortcode if !is_shortcode => {
+            shortcode(emoji).unwrap_or_else(|| emoji.to_string())
+        }
+        _ => emoji.to_string(),
+    }
+}
+
+/// Splits an emoji or `:shortcode:` prefix, and the whitespace after it, from the start
+/// of a header. Returns the prefix and the byte offset where the rest begins.
+pub fn split_prefix(header: &str) -> Option<(&str, usize)> {
+    let end = if let Some(rest) = header.strip_prefix(':') {
+        let len = rest.find(|c: char| !(c.is_ascii_alphanumeric() || "_+-".contains(c)))?;
+        if len == 0 || !rest[len..].starts_with(':') {
+            return None;
+        }
+        len + 2
+    } else {
+        let len = header
+            .find(|c: char| c.is_whitespace() || c.is_alphanumeric() || c.is_ascii())
+            .unwrap_or(header.len());
+        if len == 0 {
+            return None;
+        }
+        len
+    };
+
+    let rest = &header[end..];
+    let trimmed = rest.trim_start();
+    if trimmed.len() == rest.len() {
+        return None;
+    }
+
+    Some((&header[..end], header.len() - trimmed.len()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{format, split_prefix, GitmojiStyle};
+
+    #[test]
+    fn styles() {
+        assert_eq!(format("✨", GitmojiStyle::Shortcode), ":sparkles:");
+        assert_eq!(format(":sparkles:", GitmojiStyle::Emoji), "✨");
+        assert_eq!(format("⚡", GitmojiStyle::Shortcode), ":zap:");
+        assert_eq!(format(":unknown:", GitmojiStyle::Emoji), ":unknown:");
+    }
+
+    #[test]
+    fn prefixes() {
+        assert_eq!(split_prefix("✨ feat: x"), Some(("✨", 4)));
+        assert_eq!(split_prefix("⚡\u{fe0f} perf: x"), Some(("⚡\u{fe0f}", 7)));
+        assert_eq!(split_prefix(":sparkles: feat: x"), Some((":sparkles:", 11)));
+        assert_eq!(split_prefix("feat: x"), None);
+        assert_eq!(split_prefix(":feat: x"), Some((":feat:", 7)));
+        assert_eq!(split_prefix("✨feat: x"), None);
+        assert_eq!(split_prefix(": x"), None);
+    }
+}
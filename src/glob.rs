@@ -0,0 +1,117 @@
+//! Shell-style wildcards, for matching paths and names from the config.
+
+/// Matches a whole `path`, where `*`, `?` and `[...]` match within a directory and `**`
+/// matches any number of directories.
+pub fn matches_path(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<&str> = pattern.trim_start_matches("./").split('/').collect();
+    let path: Vec<&str> = path.split('/').collect();
+    match_dirs(&pattern, &path)
+}
+
+/// Matches a whole `name`, where `*` matches any text, `?` any char, and `[a-z]` or
+/// `[!a-z]` a char in or out of the set.
+pub fn matches(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    match_name(&pattern, &name)
+}
+
+fn match_dirs(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| match_dirs(rest, &path[skip..])),
+        Some((first, rest)) => match path.split_first() {
+            Some((dir, path)) => matches(first, dir) && match_dirs(rest, path),
+            None => false,
+        },
+    }
+}
+
+fn match_name(pattern: &[char], name: &[char]) -> bool {
+    let (n, name_rest) = match (pattern.first(), name.split_first()) {
+        (None, _) => return name.is_empty(),
+        (Some('*'), _) => {
+            return (0..=name.len()).any(|skip| match_name(&pattern[1..], &name[skip..]))
+        }
+        (Some(_), None) => return false,
+        (Some(_), Some((&n, rest))) => (n, rest),
+    };
+
+    match pattern[0] {
+        '?' => match_name(&pattern[1..], name_rest),
+        '[' => match match_class(&pattern[1..], n) {
+            Some((true, rest)) => match_name(rest, name_rest),
+            Some((false, _)) => false,
+            // An unclosed bracket is just a bracket.
+            None => n == '[' && match_name(&pattern[1..], name_rest),
+        },
+        c => c == n && match_name(&pattern[1..], name_rest),
+    }
+}
+
+/// Checks `c` against the class after a `[`, returning whether it matched and the rest
+/// of the pattern after the `]`.
+fn match_class(pattern: &[char], c: char) -> Option<(bool, &[char])> {
+    let (negated, mut i) = match pattern.first() {
+        Some('!') | Some('^') => (true, 1),
+        _ => (false, 0),
+    };
+
+    let mut matched = false;
+    let mut first = true;
+    loop {
+        match pattern.get(i)? {
+            // A `]` straight after the `[` is part of the set.
+            ']' if !first => return Some((matched != negated, &pattern[i + 1..])),
+            &start => match (pattern.get(i + 1), pattern.get(i + 2)) {
+                (Some('-'), Some(&end)) if end != ']' => {
+                    matched |= (start..=end).contains(&c);
+                    i += 3;
+                }
+                _ => {
+                    matched |= start == c;
+                    i += 1;
+                }
+            },
+        }
+        first = false;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{matches, matches_path};
+
+    #[test]
+    fn names() {
+        assert!(matches("feat", "feat"));
+        assert!(matches("*-fix", "hot-fix"));
+        assert!(matches("?ix", "fix"));
+        assert!(matches("[a-z]*", "deps"));
+        assert!(!matches("[a-z]*", "Deps"));
+        assert!(matches("[!0-9]*", "deps"));
+        assert!(!matches("[!0-9]*", "1deps"));
+        assert!(matches("[]x]", "]"));
+        assert!(matches("a[b", "a[b"));
+        assert!(!matches("feat", "feature"));
+    }
+
+    #[test]
+    fn paths() {
+        assert!(matches_path(
+            "packages/client/**",
+            "packages/client/src/api.ts"
+        ));
+        assert!(matches_path(
+            "packages/*-server/**",
+            "packages/api-server/main.go"
+        ));
+        assert!(!matches_path(
+            "packages/*-server/**",
+            "packages/server/main.go"
+        ));
+        assert!(matches_path("**/*.md", "README.md"));
+        assert!(matches_path("./docs/*.md", "docs/guide.md"));
+        assert!(!matches_path("*.md", "docs/guide.md"));
+    }
+}
@@ -1,14 +1,21 @@
+pub mod changelog;
 mod color;
-mod commitlint;
+pub mod commitlint;
 mod config;
+pub mod diff;
 pub mod figlet;
+pub mod fuzzy;
 mod git;
+pub mod gitmoji;
+mod glob;
 pub mod prompt;
+pub mod scopes;
 pub mod string;
 pub mod term_buffer;
+pub mod version;
 
-pub use commitlint::Commit;
-pub use config::Config;
+pub use commitlint::{Commit, CommitlintConfig};
+pub use config::{CommitType, Config, ConfigError, Stages};
 pub use figlet::Figlet;
-pub use git::Git;
+pub use git::{Git, GitStatus, LogItem};
 pub use term_buffer::TermBuffer;
@@ -2,11 +2,18 @@ mod cli;
 mod commands;
 
 use cli::Cli;
-use glint::Config;
+use glint::{Config, Git};
 
 fn main() {
     let command = cli::parse();
-    let config = Config::default();
+    let git = Git::from_cwd().ok();
+    let config = match Config::load(git.as_ref().map(Git::repo_root)) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
 
     match command {
         Cli::Commit(params) => {
@@ -15,5 +22,20 @@ fn main() {
         Cli::Log(params) => {
             commands::log(params, config);
         }
+        Cli::Lint(params) => {
+            commands::lint(params, config);
+        }
+        Cli::Hook(params) => {
+            commands::hook(params, config);
+        }
+        Cli::Fixup(params) => {
+            commands::fixup(params, config);
+        }
+        Cli::Changelog(params) => {
+            commands::changelog(params, config);
+        }
+        Cli::Bump(params) => {
+            commands::bump(params, config);
+        }
     }
 }
@@ -1,9 +1,17 @@
+mod breaking_prompt;
+mod commit_prompt;
+mod diff_view;
 mod files_prompt;
+mod footers_prompt;
+mod hunk_view;
 mod message_prompt;
 mod scope_prompt;
 mod type_prompt;
 
-pub use files_prompt::{FilesPrompt, FilesPromptResult};
+pub use breaking_prompt::{BreakingPrompt, BreakingPromptResult};
+pub use commit_prompt::{CommitPrompt, CommitPromptResult};
+pub use files_prompt::{ChosenFiles, FilesPrompt, FilesPromptResult};
+pub use footers_prompt::{FootersPrompt, FootersPromptResult};
 pub use message_prompt::{MessagePrompt, MessagePromptResult};
 pub use scope_prompt::{ScopePrompt, ScopePromptResult};
 pub use type_prompt::{TypePrompt, TypePromptResult};
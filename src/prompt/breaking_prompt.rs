@@ -0,0 +1,124 @@
+use crate::color::reset_display;
+use crate::string::{self, to_byte_offset, to_byte_range};
+use crate::TermBuffer;
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
+    style::{style, Color},
+};
+
+#[derive(Debug, Default)]
+pub struct BreakingPrompt {
+    breaking: bool,
+    description: String,
+    x_offset: u16,
+}
+
+pub enum BreakingPromptResult {
+    /// Whether it's a breaking change, and the optional description for the footer.
+    Breaking(bool, Option<String>),
+    Escape,
+    Terminate,
+}
+
+impl BreakingPrompt {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn run(mut self) -> BreakingPromptResult {
+        let mut buffer = TermBuffer::new();
+
+        let mut first_iteration = true;
+
+        loop {
+            let event = if first_iteration {
+                first_iteration = false;
+                None
+            } else {
+                match event::read() {
+                    Ok(Event::Key(KeyEvent { code, modifiers })) => Some((
+                        code,
+                        modifiers.contains(KeyModifiers::CONTROL),
+                        modifiers.contains(KeyModifiers::SHIFT),
+                        modifiers.contains(KeyModifiers::ALT),
+                    )),
+                    _ => continue,
+                }
+            };
+
+            // First we ask the yes/no question, and only then the description.
+            match (self.breaking, event) {
+                (_, Some((KeyCode::Char('c'), true, false, false))) => {
+                    return BreakingPromptResult::Terminate;
+                }
+                (false, Some((KeyCode::Char('y'), false, _, false)))
+                | (false, Some((KeyCode::Char('Y'), false, _, false))) => {
+                    self.breaking = true;
+                }
+                (false, Some((KeyCode::Char('n'), false, _, false)))
+                | (false, Some((KeyCode::Char('N'), false, _, false)))
+                | (false, Some((KeyCode::Enter, _, _, _))) => {
+                    return BreakingPromptResult::Breaking(false, None);
+                }
+                (false, Some((KeyCode::Esc, _, _, _))) => {
+                    return BreakingPromptResult::Escape;
+                }
+                (true, Some((KeyCode::Enter, _, _, _))) => {
+                    let description = self.description.trim().to_string();
+                    return BreakingPromptResult::Breaking(
+                        true,
+                        Some(description).filter(|s| !s.is_empty()),
+                    );
+                }
+                (true, Some((KeyCode::Esc, _, _, _))) => {
+                    self.breaking = false;
+                }
+                (true, Some((KeyCode::Char(c), false, _, false))) if c > '\x1F' => {
+                    let offset = to_byte_offset(&self.description, self.x_offset as usize);
+                    self.description.insert(offset, c);
+                    self.x_offset += 1;
+                }
+                (true, Some((KeyCode::Backspace, false, _, false))) if self.x_offset > 0 => {
+                    let range = to_byte_range(&self.description, self.x_offset as usize - 1);
+                    self.description.replace_range(range, "");
+                    self.x_offset -= 1;
+                }
+                (true, Some((KeyCode::Left, false, _, false))) => {
+                    self.x_offset = self.x_offset.saturating_sub(1);
+                }
+                (true, Some((KeyCode::Right, false, _, false))) => {
+                    if (self.x_offset as usize) < string::len(&self.description) {
+                        self.x_offset += 1;
+                    }
+                }
+                (_, None) => {}
+                _ => continue,
+            };
+
+            let question = "Is this a breaking change? (y/N) ";
+            let answer = if self.breaking { "yes" } else { "" };
+            buffer.push_line(format!(
+                "{}{}{}",
+                question,
+                style(answer).with(crate::color::theme_user_input()),
+                reset_display()
+            ));
+
+            if self.breaking {
+                let instructions = "Describe what breaks and how to migrate (optional):";
+                buffer.push_line(instructions);
+                buffer.push_line(format!(
+                    "{}{}",
+                    style(&self.description).with(Color::Red),
+                    reset_display()
+                ));
+                buffer.set_next_cursor((self.x_offset, 2));
+            } else {
+                buffer.set_next_cursor((question.len() as u16, 0));
+            }
+
+            buffer.render_frame();
+            buffer.flush();
+        }
+    }
+}
@@ -0,0 +1,222 @@
+use crate::color::reset_display;
+use crate::fuzzy::fuzzy_match;
+use crate::{Config, LogItem, TermBuffer};
+use crossterm::{
+    self as ct,
+    event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
+    style::{style, Color},
+};
+use std::cmp::Reverse;
+use std::fmt::Write as _;
+
+/// Picks one of a list of commits, e.g. the target of a fixup.
+#[derive(Debug)]
+pub struct CommitPrompt<'a> {
+    config: &'a Config,
+    title: &'a str,
+    logs: &'a [LogItem],
+    input: String,
+    /// Index into the filtered commits.
+    focused_index: usize,
+}
+
+pub enum CommitPromptResult {
+    /// The index of the chosen commit in the list passed to `new`.
+    Commit(usize),
+    Escape,
+    Terminate,
+}
+
+/// The most commits listed at once. Fewer are shown on short terminals.
+const MAX_VISIBLE: usize = 15;
+
+impl<'a> CommitPrompt<'a> {
+    pub fn new(config: &'a Config, title: &'a str, logs: &'a [LogItem]) -> Self {
+        CommitPrompt {
+            config,
+            title,
+            logs,
+            input: String::new(),
+            focused_index: 0,
+        }
+    }
+
+    /// The indices of the commits whose header fuzzy matches the input, best first. Without
+    /// input, all of them, newest first.
+    fn filter_commits(&self) -> Vec<usize> {
+        let mut scored: Vec<(i64, usize)> = self
+            .logs
+            .iter()
+            .enumerate()
+            .filter_map(|(i, log)| {
+                let m = fuzzy_match(&self.input, header(log))?;
+                Some((m.score, i))
+            })
+            .collect();
+
+        // Stable, so equally good matches stay newest first.
+        scored.sort_by_key(|&(score, _)| Reverse(score));
+        scored.into_iter().map(|(_, i)| i).collect()
+    }
+
+    pub fn run(mut self) -> CommitPromptResult {
+        let mut buffer = TermBuffer::new();
+
+        let mut first_iteration = true;
+        loop {
+            let event = if first_iteration {
+                first_iteration = false;
+                None
+            } else {
+                match event::read() {
+                    Ok(Event::Key(KeyEvent { code, modifiers })) => Some((
+                        code,
+                        modifiers.contains(KeyModifiers::CONTROL),
+                        modifiers.contains(KeyModifiers::SHIFT),
+                        modifiers.contains(KeyModifiers::ALT),
+                    )),
+                    _ => continue,
+                }
+            };
+
+            match event {
+                Some((KeyCode::Char('c'), true, false, false)) => {
+                    return CommitPromptResult::Terminate;
+                }
+                Some((KeyCode::Enter, false, false, false)) => {
+                    if let Some(&i) = self.filter_commits().get(self.focused_index) {
+                        return CommitPromptResult::Commit(i);
+                    }
+                }
+                Some((KeyCode::Char(c), false, _, false)) => {
+                    self.input.push(c);
+                    self.focused_index = 0;
+                }
+                Some((KeyCode::Backspace, false, _, false)) => {
+                    self.input.pop();
+                    self.focused_index = 0;
+                }
+                Some((KeyCode::Esc, false, _, false)) => {
+                    return CommitPromptResult::Escape;
+                }
+                Some((KeyCode::Up, false, _, false)) => {
+                    self.focused_index = self.focused_index.saturating_sub(1);
+                }
+                Some((KeyCode::Down, false, _, false)) => {
+                    let total = self.filter_commits().len();
+                    self.focused_index = (self.focused_index + 1).min(total.saturating_sub(1));
+                }
+                None => {}
+                _ => continue,
+            };
+
+            let (width, height) = ct::terminal::size().expect("get terminal size");
+            let width = width.max(60);
+            let visible = MAX_VISIBLE.min((height as usize).saturating_sub(4).max(1));
+
+            buffer.push_line("");
+            buffer.push_line(format!(
+                "{}{}{}{}",
+                self.title,
+                style(&self.input).with(crate::color::theme_user_input()),
+                style("_".repeat(6_usize.saturating_sub(self.input.len())))
+                    .with(crate::color::theme_user_input()),
+                reset_display()
+            ));
+            let cursor_x = self.title.len() + self.input.len();
+
+            // Scroll so the focused commit is always shown.
+            let commits = self.filter_commits();
+            let skip = (self.focused_index + 1).saturating_sub(visible);
+            for (i, &log) in commits.iter().enumerate().skip(skip).take(visible) {
+                let line = self.render(&self.logs[log], i == self.focused_index, width as usize);
+                buffer.push_line(line);
+            }
+
+            if commits.is_empty() {
+                buffer.push_line(format!(
+                    "{}{}",
+                    style(format!("No commit matches '{}'", self.input)).with(Color::Red),
+                    reset_display()
+                ));
+            }
+
+            buffer.set_next_cursor((cursor_x as u16, 1));
+            buffer.render_frame();
+            buffer.flush();
+        }
+    }
+
+    /// A line like `glint log` prints, with a marker for the focused commit.
+    fn render(&self, log: &LogItem, focused: bool, width: usize) -> String {
+        let mut line = String::new();
+        let marker = if focused {
+            style("*").with(Color::Blue)
+        } else {
+            style("-").with(Color::Reset)
+        };
+        write!(
+            line,
+            "{} {} ",
+            marker,
+            style(&log.commit[..8]).with(Color::Yellow)
+        )
+        .unwrap();
+
+        // The hash and marker take 11 columns.
+        let width = width.saturating_sub(11);
+        let conv = match log.as_conventional() {
+            Ok(conv) => conv,
+            Err(_) => {
+                let header = crate::string::split_at(header(log), width).0;
+                write!(line, "{}{}", header, reset_display()).unwrap();
+                return line;
+            }
+        };
+
+        let ty_color = self
+            .config
+            .find_type(conv.ty)
+            .and_then(|ty| ty.color)
+            .unwrap_or(Color::Magenta);
+        let mut prefix_len = conv.ty.len() + 2;
+
+        if let Some(emoji) = conv.emoji {
+            write!(line, "{} ", emoji).unwrap();
+            prefix_len += crate::string::len(emoji) + 1;
+        }
+        write!(line, "{}", style(conv.ty).with(ty_color)).unwrap();
+        if let Some(scope) = conv.scope {
+            write!(
+                line,
+                "{}{}{}",
+                style("(").with(Color::Grey),
+                style(scope).with(Color::Blue),
+                style(")").with(Color::Grey)
+            )
+            .unwrap();
+            prefix_len += scope.len() + 2;
+        }
+        if conv.breaking {
+            write!(line, "{}", style("!").with(Color::Red)).unwrap();
+            prefix_len += 1;
+        }
+
+        let subject = crate::string::split_at(conv.subject, width.saturating_sub(prefix_len)).0;
+        write!(
+            line,
+            "{}{}{}",
+            style(": ").with(Color::Grey),
+            subject,
+            reset_display()
+        )
+        .unwrap();
+
+        line
+    }
+}
+
+/// The first line of the commit's message.
+fn header(log: &LogItem) -> &str {
+    log.message.lines().next().unwrap_or("")
+}
@@ -0,0 +1,202 @@
+use crate::color::reset_display;
+use crate::diff::{self, LineKind};
+use crate::string;
+use crate::TermBuffer;
+use crossterm::{
+    event::KeyCode,
+    style::{style, Attribute, Color},
+};
+use std::ops::Range;
+
+/// A scrollable, colored diff. Shown by `FilesPrompt` in place of its file list.
+#[derive(Debug, Clone)]
+pub struct DiffView {
+    title: String,
+    rows: Vec<Row>,
+    scroll: usize,
+    /// The number of rows that fit on the screen, as of the last render.
+    page: usize,
+}
+
+#[derive(Debug, Clone)]
+enum Row {
+    File(String),
+    /// Header lines such as `new file mode` or `Binary files differ`.
+    Meta(String),
+    Hunk(String),
+    Line {
+        kind: LineKind,
+        text: String,
+        /// The byte ranges of `text` that differ from the line it replaces.
+        words: Vec<Range<usize>>,
+    },
+    NoNewline,
+    Blank,
+}
+
+impl DiffView {
+    pub fn new(title: impl Into<String>, diff: &str) -> Self {
+        let mut rows = vec![];
+
+        for file in diff::parse(diff) {
+            if !rows.is_empty() {
+                rows.push(Row::Blank);
+            }
+            rows.push(Row::File(file.path().to_string()));
+
+            let skipped = ["diff ", "index ", "--- ", "+++ "];
+            for line in &file.header {
+                if !skipped.iter().any(|prefix| line.starts_with(prefix)) {
+                    rows.push(Row::Meta(line.clone()));
+                }
+            }
+
+            for hunk in &file.hunks {
+                rows.push(Row::Hunk(format!(
+                    "@@ -{},{} +{},{} @@ {}",
+                    hunk.old_start, hunk.old_len, hunk.new_start, hunk.new_len, hunk.section
+                )));
+
+                for (line, words) in hunk.lines.iter().zip(hunk.changed_words()) {
+                    rows.push(Row::Line {
+                        kind: line.kind,
+                        text: line.text.clone(),
+                        words,
+                    });
+                    if line.no_newline {
+                        rows.push(Row::NoNewline);
+                    }
+                }
+            }
+        }
+
+        if rows.is_empty() {
+            rows.push(Row::Meta("No changes".into()));
+        }
+
+        DiffView {
+            title: title.into(),
+            rows,
+            scroll: 0,
+            page: 1,
+        }
+    }
+
+    /// Scrolls the view. Returns true when it should be closed.
+    pub fn handle_key(&mut self, code: KeyCode) -> bool {
+        let last = self.rows.len().saturating_sub(self.page);
+
+        self.scroll = match code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Enter => return true,
+            KeyCode::Up | KeyCode::Char('k') => self.scroll.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.scroll + 1,
+            KeyCode::PageUp | KeyCode::Char('b') => self.scroll.saturating_sub(self.page),
+            KeyCode::PageDown | KeyCode::Char(' ') | KeyCode::Char('f') => self.scroll + self.page,
+            KeyCode::Home | KeyCode::Char('g') => 0,
+            KeyCode::End | KeyCode::Char('G') => last,
+            _ => self.scroll,
+        }
+        .min(last);
+
+        false
+    }
+
+    /// Pushes the visible rows onto `buffer`, using `height` lines. Returns the line to put
+    /// the cursor on.
+    pub fn render(&mut self, buffer: &mut TermBuffer, width: usize, height: usize) -> u16 {
+        buffer.push_line(format!(
+            "{}{}",
+            style(string::split_at(&self.title, width).0).attribute(Attribute::Bold),
+            reset_display()
+        ));
+
+        self.page = height.saturating_sub(2).max(1);
+        self.scroll = self.scroll.min(self.rows.len().saturating_sub(self.page));
+
+        let shown = self.rows.iter().skip(self.scroll).take(self.page);
+        let count = shown.len();
+        for row in shown {
+            buffer.push_line(render_row(row, width));
+        }
+
+        let status = format!(
+            "lines {}-{} of {} (arrows, PgUp/PgDn and Home/End scroll, q closes)",
+            self.scroll + 1,
+            self.scroll + count,
+            self.rows.len()
+        );
+        buffer.push_line(format!(
+            "{}{}",
+            style(string::split_at(&status, width).0).with(Color::DarkGrey),
+            reset_display()
+        ));
+
+        buffer.lines() - 1
+    }
+}
+
+fn render_row(row: &Row, width: usize) -> String {
+    let line = match row {
+        Row::File(path) => style(string::split_at(path, width).0)
+            .attribute(Attribute::Bold)
+            .to_string(),
+        Row::Meta(text) => style(string::split_at(text, width).0)
+            .attribute(Attribute::Bold)
+            .with(Color::DarkGrey)
+            .to_string(),
+        Row::Hunk(text) => style(string::split_at(text, width).0)
+            .with(Color::Cyan)
+            .to_string(),
+        Row::Line { kind, text, words } => {
+            let (sign, color, highlight) = match kind {
+                LineKind::Added => ('+', Color::Green, Color::DarkGreen),
+                LineKind::Removed => ('-', Color::Red, Color::DarkRed),
+                LineKind::Context => (' ', Color::Reset, Color::Reset),
+            };
+            let mut line = style(sign).with(color).to_string();
+            for (segment, changed) in segments(text, words, width.saturating_sub(1)) {
+                let segment = style(segment).with(color);
+                if changed {
+                    line.push_str(&segment.on(highlight).to_string());
+                } else {
+                    line.push_str(&segment.to_string());
+                }
+            }
+            line
+        }
+        Row::NoNewline => style("\\ No newline at end of file")
+            .with(Color::DarkGrey)
+            .to_string(),
+        Row::Blank => String::new(),
+    };
+
+    format!("{}{}", line, reset_display())
+}
+
+/// Splits `text` into runs that are or aren't in `words`, expanding tabs and stopping
+/// after `width` chars.
+fn segments(text: &str, words: &[Range<usize>], width: usize) -> Vec<(String, bool)> {
+    let mut segments: Vec<(String, bool)> = vec![];
+    let mut columns = 0;
+
+    for (i, c) in text.char_indices() {
+        let changed = words.iter().any(|range| range.contains(&i));
+        let expanded = if c == '\t' {
+            "    ".into()
+        } else {
+            c.to_string()
+        };
+
+        columns += if c == '\t' { 4 } else { 1 };
+        if columns > width {
+            break;
+        }
+
+        match segments.last_mut() {
+            Some((segment, was_changed)) if *was_changed == changed => segment.push_str(&expanded),
+            _ => segments.push((expanded, changed)),
+        }
+    }
+
+    segments
+}
@@ -1,10 +1,16 @@
+use super::diff_view::DiffView;
+use super::hunk_view::{HunkView, HunkViewAction, Selection};
 use crate::color::reset_display;
+use crate::diff::{self, Patch};
+use crate::fuzzy::fuzzy_match;
 use crate::git::{Git, GitStatus, GitStatusItem, GitStatusType};
 use crate::Config;
 use crate::TermBuffer;
 use crossterm::{
+    self as ct,
     event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
     style::{style, Color},
+    terminal,
 };
 use std::iter;
 
@@ -12,28 +18,281 @@ use std::iter;
 pub struct FilesPrompt<'a> {
     config: &'a Config,
     checked: Vec<bool>,
-    focused_index: u16,
+    /// Index into the rows, where 0 is `<all>`.
+    focused_index: usize,
+    /// The first row shown, when they don't all fit.
+    scroll: usize,
+    /// Only files whose name fuzzy matches this are listed.
+    filter: String,
+    /// Whether keys are typed into the filter.
+    filtering: bool,
     options: GitStatus,
     git: &'a Git,
+    /// The changes chosen in the files that are only partly committed.
+    hunks: Vec<Option<HunkView>>,
+    /// Files committed with what's staged, leaving their unstaged changes out.
+    keep: Vec<bool>,
+    /// The file whose hunks are being chosen, replacing the list.
+    view: Option<(usize, HunkView)>,
+    /// The diff being shown, replacing the list.
+    diff_view: Option<DiffView>,
 }
 
 pub enum FilesPromptResult {
-    Files(Vec<String>),
+    Files(ChosenFiles),
     Escape,
     Terminate,
 }
 
+/// How the index should change before committing.
+#[derive(Debug, Clone, Default)]
+pub struct ChosenFiles {
+    /// Every file that will be committed, in full or in part.
+    pub files: Vec<String>,
+    /// Whole files to stage.
+    pub add: Vec<String>,
+    /// The changes chosen from the files that are only partly committed.
+    pub patch: Option<Patch>,
+    /// Staged files that were unchecked.
+    pub unstage: Vec<String>,
+}
+
 impl<'a> FilesPrompt<'a> {
     pub fn new(config: &'a Config, git: &'a Git, options: GitStatus) -> Self {
+        // Staged files start checked, unless they also have unstaged changes, in which
+        // case just the staged ones are kept.
         FilesPrompt {
             config,
-            checked: (0..options.len()).map(|_| false).collect(),
+            checked: options
+                .iter()
+                .map(|item| item.is_staged() && !item.is_unstaged())
+                .collect(),
             focused_index: 0,
+            scroll: 0,
+            filter: String::new(),
+            filtering: false,
+            hunks: (0..options.len()).map(|_| None).collect(),
+            keep: options
+                .iter()
+                .map(|item| item.is_staged() && item.is_unstaged())
+                .collect(),
+            view: None,
+            diff_view: None,
             options,
             git,
         }
     }
 
+    /// Shows the hunks of the file at `index`, starting with the changes chosen before.
+    fn open_hunks(&mut self, index: usize) {
+        if let Some(ref view) = self.hunks[index] {
+            self.view = Some((index, view.clone()));
+            return;
+        }
+
+        // Conflicts can't be applied in parts.
+        let item = self.item(index);
+        if item.is_dir() || item.conflict().is_some() {
+            return;
+        }
+
+        let diff = match self.git.diff_file(item.file_name(), item.is_new(), false) {
+            Ok(diff) => diff,
+            Err(_) => return,
+        };
+        if let Some(diff) = diff::parse(&diff).into_iter().next() {
+            self.view = Some((index, HunkView::new(diff, self.checked[index])));
+        }
+    }
+
+    /// Commits the whole file if every change was chosen, or else the chosen changes.
+    fn choose_hunks(&mut self, index: usize, view: HunkView) {
+        // Binary files can only be toggled as a whole.
+        if view.diff.hunks.is_empty() {
+            return;
+        }
+
+        // The chosen changes are staged on top of what already is.
+        let staged = self.item(index).is_staged();
+        match view.selection() {
+            Selection::None => {
+                self.checked[index] = false;
+                self.hunks[index] = None;
+                self.keep[index] = staged;
+            }
+            Selection::All => {
+                self.checked[index] = true;
+                self.hunks[index] = None;
+                self.keep[index] = false;
+            }
+            Selection::Some => {
+                self.checked[index] = false;
+                self.hunks[index] = Some(view);
+                self.keep[index] = staged;
+            }
+        }
+    }
+
+    fn item(&self, index: usize) -> &GitStatusItem {
+        self.options
+            .iter()
+            .nth(index)
+            .expect("index should match a file")
+    }
+
+    /// Whether the file at `index` is committed only in part.
+    fn is_partial(&self, index: usize) -> bool {
+        self.hunks[index].is_some() || self.keep[index]
+    }
+
+    /// The indices of the files listed after `<all>`, i.e. those matching the filter.
+    fn rows(&self) -> Vec<usize> {
+        self.options
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| fuzzy_match(&self.filter, item.file_name()).is_some())
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// The diff of the row at `row`, where 0 is `<all>`, and a title for it. `color` is for
+    /// pagers; the built-in viewer highlights the plain diff itself.
+    fn diff(&self, row: usize, color: bool) -> (String, String) {
+        let rows = self.rows();
+        let (title, items): (String, Vec<&GitStatusItem>) = match row {
+            0 if self.filter.is_empty() => ("All changes".into(), self.options.iter().collect()),
+            0 => (
+                format!("Changes matching '{}'", self.filter),
+                rows.iter().map(|&i| self.item(i)).collect(),
+            ),
+            _ => {
+                let item = self.item(rows[row - 1]);
+                (item.file_name().to_string(), vec![item])
+            }
+        };
+
+        // The staged changes come first, with a renamed file's old path so that git pairs
+        // them up, followed by the unstaged changes to tracked files.
+        let staged: Vec<&str> = items
+            .iter()
+            .filter(|item| item.is_staged())
+            .flat_map(|item| iter::once(item.file_name()).chain(item.orig_name()))
+            .collect();
+        let tracked: Vec<&str> = items
+            .iter()
+            .filter(|item| !item.is_new() && item.is_unstaged())
+            .map(|item| item.file_name())
+            .collect();
+
+        let mut diff = String::new();
+        if !staged.is_empty() {
+            diff.push_str(&self.git.diff_cached(staged, color).unwrap_or_default());
+        }
+        if !tracked.is_empty() {
+            diff.push_str(&self.git.diff(tracked, color).unwrap_or_default());
+        }
+
+        for item in items.iter().filter(|item| item.is_new()) {
+            let files = if item.is_dir() {
+                self.git
+                    .untracked_files(item.file_name())
+                    .unwrap_or_default()
+            } else {
+                vec![item.file_name().to_string()]
+            };
+            for file in files {
+                diff.push_str(&self.git.diff_file(&file, true, color).unwrap_or_default());
+            }
+        }
+
+        (title, diff)
+    }
+
+    /// Shows a diff in the built-in viewer, or the configured pager.
+    fn show_diff(&mut self, row: usize) {
+        let pager = match self.config.pager.as_deref() {
+            None | Some("builtin") => {
+                let (title, diff) = self.diff(row, false);
+                self.diff_view = Some(DiffView::new(title, &diff));
+                return;
+            }
+            Some("git") => self.git.pager(),
+            Some(pager) => pager.to_string(),
+        };
+        let (_, diff) = self.diff(row, true);
+
+        let _r = terminal::disable_raw_mode();
+        let _r = self.git.page(&pager, &diff);
+        let _r = terminal::enable_raw_mode();
+    }
+
+    /// The chosen changes of the partly committed files.
+    fn patch(&self) -> Option<Patch> {
+        let views: Vec<&HunkView> = self.hunks.iter().flatten().collect();
+        if views.is_empty() {
+            return None;
+        }
+
+        Some(Patch {
+            files: views
+                .iter()
+                .map(|view| view.diff.path().to_string())
+                .collect(),
+            text: views.iter().filter_map(|view| view.patch()).collect(),
+        })
+    }
+
+    /// What to stage and unstage to commit the checked files and chosen changes.
+    fn chosen(&self) -> ChosenFiles {
+        let mut chosen = ChosenFiles {
+            patch: self.patch(),
+            ..Default::default()
+        };
+
+        for (i, item) in self.options.iter().enumerate() {
+            let name = item.file_name().to_string();
+            if self.checked[i] {
+                if item.is_unstaged() {
+                    chosen.add.push(name.clone());
+                }
+                chosen.files.push(name);
+            } else if self.is_partial(i) {
+                chosen.files.push(name);
+            } else if item.is_staged() {
+                // Both sides of a rename need unstaging.
+                chosen.unstage.extend(item.orig_name().map(String::from));
+                chosen.unstage.push(name);
+            }
+        }
+
+        chosen
+    }
+
+    /// Toggles the file at `row`, or every listed file for `<all>`.
+    fn toggle(&mut self, row: usize) {
+        let files = match row {
+            0 => self.rows(),
+            _ => vec![self.rows()[row - 1]],
+        };
+        let set_to = !files.iter().all(|&i| self.checked[i]);
+
+        for i in files {
+            self.checked[i] = set_to;
+            self.hunks[i] = None;
+            self.keep[i] = false;
+        }
+    }
+
+    /// Moves the focus to `row`, scrolling so that it stays within the `page` rows shown.
+    fn focus(&mut self, row: usize, page: usize) {
+        self.focused_index = row.min(self.rows().len());
+        if self.focused_index < self.scroll {
+            self.scroll = self.focused_index;
+        } else if self.focused_index >= self.scroll + page {
+            self.scroll = self.focused_index + 1 - page;
+        }
+    }
+
     pub fn run(mut self) -> FilesPromptResult {
         let mut buffer = TermBuffer::new();
 
@@ -42,10 +301,8 @@ impl<'a> FilesPrompt<'a> {
             .get_figlet()
             .expect("Ensure figlet_file points to a valid file, or remove it.");
 
-        // Padded limit (never overflows by 1 item)
-        let total = self.options.len();
-        let max = 15;
-        let take = if total > max { max - 3 } else { total };
+        // The number of rows shown, as of the last render.
+        let mut page = 1;
 
         let mut first_iteration = true;
         loop {
@@ -64,7 +321,71 @@ impl<'a> FilesPrompt<'a> {
                 }
             };
 
-            if let Some((ref mut key, _, _, _)) = event {
+            if let Some(ref mut view) = self.diff_view {
+                if let Some((code, ctrl, _, _)) = event {
+                    if code == KeyCode::Char('c') && ctrl {
+                        return FilesPromptResult::Terminate;
+                    }
+                    if view.handle_key(code) {
+                        self.diff_view = None;
+                    }
+                }
+
+                // The key was for the diff, not the list.
+                event = None;
+            }
+
+            if self.view.is_some() {
+                if let Some((code, ctrl, _, _)) = event {
+                    if code == KeyCode::Char('c') && ctrl {
+                        return FilesPromptResult::Terminate;
+                    }
+
+                    let action = match self.view {
+                        Some((_, ref mut view)) => view.handle_key(code),
+                        None => None,
+                    };
+                    match action {
+                        Some(HunkViewAction::Done) => {
+                            if let Some((index, view)) = self.view.take() {
+                                self.choose_hunks(index, view);
+                            }
+                        }
+                        Some(HunkViewAction::Cancel) => self.view = None,
+                        None => {}
+                    }
+                }
+
+                // The key was for the hunks, not the list.
+                event = None;
+            }
+
+            if self.filtering {
+                match event {
+                    Some((KeyCode::Char(c), false, _, false)) => {
+                        self.filter.push(c);
+                        self.focus(0, page);
+                        event = None;
+                    }
+                    Some((KeyCode::Backspace, false, _, false)) => {
+                        self.filter.pop();
+                        self.focus(0, page);
+                        event = None;
+                    }
+                    // Enter keeps the filter, and Esc clears it.
+                    Some((KeyCode::Enter, _, _, _)) => {
+                        self.filtering = false;
+                        event = None;
+                    }
+                    Some((KeyCode::Esc, _, _, _)) => {
+                        self.filtering = false;
+                        self.filter.clear();
+                        self.focus(0, page);
+                        event = None;
+                    }
+                    _ => {}
+                }
+            } else if let Some((ref mut key, _, _, _)) = event {
                 // Vim-like navigation, since this prompt doesn't have text input
                 // The right arrow strokes are also aliased to the diff shortcut, since
                 // it's like going deeper into the tree
@@ -72,92 +393,90 @@ impl<'a> FilesPrompt<'a> {
                     KeyCode::Char('q') => KeyCode::Esc,
                     KeyCode::Char('k') => KeyCode::Up,
                     KeyCode::Char('j') => KeyCode::Down,
+                    KeyCode::Char('g') => KeyCode::Home,
+                    KeyCode::Char('G') => KeyCode::End,
                     KeyCode::Char('l') | KeyCode::Right => KeyCode::Char('d'),
                     _ => *key,
                 };
             }
 
+            let last = self.rows().len();
+
             match event {
                 Some((KeyCode::Char('c'), true, false, false)) => {
                     return FilesPromptResult::Terminate;
                 }
                 Some((KeyCode::Char(' '), false, _, false)) => {
-                    let index = self.focused_index as usize;
-                    if index == 0 {
-                        let set_to = !self.checked.iter().all(|&x| x);
-
-                        for item in self.checked.iter_mut() {
-                            *item = set_to;
-                        }
-                    } else {
-                        self.checked[index - 1] = !self.checked[index - 1];
+                    self.toggle(self.focused_index);
+                }
+                Some((KeyCode::Char('p'), false, _, false)) => {
+                    if self.focused_index > 0 {
+                        let index = self.rows()[self.focused_index - 1];
+                        self.open_hunks(index);
                     }
                 }
+                Some((KeyCode::Char('/'), false, _, false)) => {
+                    self.filtering = true;
+                }
 
                 Some((KeyCode::Char('d'), _, _, _)) => {
-                    let index = self.focused_index as usize;
-                    if index == 0 {
-                        let files: Vec<String> = vec![];
-                        let _r = self.git.diff_less(files);
-                    } else {
-                        let option = self
-                            .options
-                            .iter()
-                            .nth(index - 1)
-                            .expect("diff should match a file");
-
-                        if option.is_new() {
-                            if option.is_dir() {
-                                let _r = self
-                                    .git
-                                    .directory_untracked_less(option.file_name().as_ref());
-                            } else {
-                                let _r = self.git.less(option.file_name());
-                            }
-                        } else {
-                            let files = vec![option.file_name().to_string()];
-                            let _r = self.git.diff_less(files);
-                        }
-                    }
+                    self.show_diff(self.focused_index);
                 }
                 Some((KeyCode::Enter, _, _, _)) => {
-                    let selected: Vec<String> = self
-                        .options
-                        .iter()
-                        .enumerate()
-                        .filter_map(|(i, file)| Some(file).filter(|_| self.checked[i]))
-                        .map(Into::into)
-                        .collect();
-                    if !selected.is_empty() {
-                        return FilesPromptResult::Files(selected);
+                    let chosen = self.chosen();
+                    if !chosen.files.is_empty() {
+                        return FilesPromptResult::Files(chosen);
                     }
                 }
 
+                Some((KeyCode::Esc, _, _, _)) if !self.filter.is_empty() => {
+                    self.filter.clear();
+                    self.focus(0, page);
+                }
                 Some((KeyCode::Esc, _, _, _)) => {
                     return FilesPromptResult::Escape;
                 }
-                Some((KeyCode::Up, _, _, true)) => {
-                    self.focused_index = 0;
+                Some((KeyCode::Up, _, _, true)) | Some((KeyCode::Home, _, _, _)) => {
+                    self.focus(0, page);
                 }
                 Some((KeyCode::Up, _, _, false)) => {
-                    self.focused_index = match self.focused_index {
-                        0 => 0,
-                        x => x.saturating_sub(1),
-                    };
+                    self.focus(self.focused_index.saturating_sub(1), page);
                 }
-                Some((KeyCode::Down, _, _, true)) => {
-                    self.focused_index += take as u16;
+                Some((KeyCode::Down, _, _, true)) | Some((KeyCode::End, _, _, _)) => {
+                    self.focus(last, page);
                 }
                 Some((KeyCode::Down, _, _, false)) => {
-                    self.focused_index += 1;
-                    if self.focused_index >= take as u16 + 1 {
-                        self.focused_index = take as u16;
-                    }
+                    self.focus(self.focused_index + 1, page);
+                }
+                Some((KeyCode::PageUp, _, _, _)) => {
+                    self.focus(self.focused_index.saturating_sub(page), page);
+                }
+                Some((KeyCode::PageDown, _, _, _)) => {
+                    self.focus(self.focused_index + page, page);
                 }
                 None => {}
                 _ => continue,
             };
 
+            let (width, height) = ct::terminal::size().expect("get terminal size");
+            let (width, height) = (width.max(60) as usize, height.max(10) as usize);
+
+            if let Some(ref mut view) = self.diff_view {
+                let y = view.render(&mut buffer, width, height - 1);
+                buffer.set_next_cursor((0, y));
+                buffer.render_frame();
+                buffer.flush();
+                continue;
+            }
+
+            if let Some((_, ref view)) = self.view {
+                let y = view.render(&mut buffer, width, height - 1);
+                buffer.set_next_cursor((0, y));
+                buffer.render_frame();
+                buffer.flush();
+                continue;
+            }
+
             let mut header = figlet.create_vec();
             figlet.write_to_buf_color("<glint>", header.as_mut_slice(), |s| {
                 style(s).with(Color::Magenta).to_string()
@@ -167,18 +486,29 @@ impl<'a> FilesPrompt<'a> {
                 buffer.push_line(line);
             }
 
-            let prompt_pre = "Toggle files to commit (with <space>, or tap 'd' for diff):";
+            let prompt_pre =
+                "Toggle files to commit (with <space>, 'p' to pick changes, or tap 'd' for diff):";
+            let legend =
+                "The columns show the staged and unstaged changes; ◩ keeps only part of a file.";
             let underscores = "-".repeat(prompt_pre.len());
             buffer.push_line("");
             buffer.push_line(prompt_pre);
+            buffer.push_line(format!(
+                "{}{}",
+                style(legend).with(Color::DarkGrey),
+                reset_display()
+            ));
             buffer.push_line(format!("{}{}", underscores, reset_display()));
 
-            let y_offset = buffer.lines() + self.focused_index;
+            // Fill the terminal, leaving a line for the footer and one for the cursor.
+            page = height.saturating_sub(buffer.lines() as usize + 2).max(3);
+            self.focus(self.focused_index, page);
+            let y_offset = buffer.lines() + (self.focused_index - self.scroll) as u16;
 
             let focused_color = Color::Blue;
             let default_color = Color::Reset;
 
-            let status_untracked = style('+').with(Color::Rgb {
+            let status_added = style('+').with(Color::Rgb {
                 r: 96,
                 g: 218,
                 b: 177,
@@ -193,51 +523,109 @@ impl<'a> FilesPrompt<'a> {
                 g: 96,
                 b: 118,
             });
+            let status_renamed = style('→').with(Color::Rgb {
+                r: 218,
+                g: 186,
+                b: 96,
+            });
+            let status_unmerged = style('!').with(Color::Rgb {
+                r: 218,
+                g: 96,
+                b: 118,
+            });
             let status_none = style(' ');
 
-            for (i, git_status_item) in iter::once(&GitStatusItem::new("<all>".to_owned()))
-                .chain(self.options.iter().map(|item| item))
-                .enumerate()
-                .take(take + 1)
-            {
-                let line_color = if i as u16 == self.focused_index {
+            let rows = self.rows();
+            let all = GitStatusItem::new("<all>".to_owned());
+            let items =
+                iter::once((None, &all)).chain(rows.iter().map(|&i| (Some(i), self.item(i))));
+
+            for (row, (index, git_status_item)) in items.enumerate().skip(self.scroll).take(page) {
+                let line_color = if row == self.focused_index {
                     focused_color
                 } else {
                     default_color
                 };
 
-                let checked = if i == 0 {
-                    self.checked.iter().all(|&x| x)
-                } else {
-                    self.checked[i - 1]
+                let (checked, partial) = match index {
+                    None => (rows.iter().all(|&i| self.checked[i]), false),
+                    Some(i) => (self.checked[i], self.is_partial(i)),
                 };
-                let prefix = style(if checked { '☑' } else { '□' }).with(line_color);
+                let prefix = style(match (checked, partial) {
+                    (true, _) => '☑',
+                    (false, true) => '◩',
+                    (false, false) => '□',
+                })
+                .with(line_color);
 
-                let file_status = match *git_status_item.status() {
-                    GitStatusType::Untracked => &status_untracked,
-                    GitStatusType::Modified => &status_modified,
+                let symbol = |status: &GitStatusType| match status {
+                    GitStatusType::Added | GitStatusType::Copied | GitStatusType::Untracked => {
+                        &status_added
+                    }
+                    GitStatusType::Modified | GitStatusType::TypeChanged => &status_modified,
+                    GitStatusType::Renamed => &status_renamed,
                     GitStatusType::Deleted => &status_deleted,
-                    _ => &status_none,
+                    GitStatusType::Unmerged => &status_unmerged,
+                    GitStatusType::None => &status_none,
                 };
+                let index_status = symbol(git_status_item.index_status());
+                let worktree_status = symbol(git_status_item.worktree_status());
 
-                let file_name = style(git_status_item.file_name()).with(line_color);
+                let file_name = match git_status_item.orig_name() {
+                    Some(orig_name) => format!("{} → {}", orig_name, git_status_item.file_name()),
+                    None => git_status_item.file_name().to_string(),
+                };
+                let file_name = style(file_name).with(line_color);
 
                 let line = format!(
-                    "{} {} {}{}",
+                    "{} {}{} {}{}",
                     prefix,
-                    file_status,
+                    index_status,
+                    worktree_status,
                     file_name,
                     reset_display(),
                 );
                 buffer.push_line(line);
             }
 
-            if take < total {
-                let diff = total - take;
-                buffer.push_line(format!("and {} more", diff));
+            if rows.is_empty() {
+                buffer.push_line(format!(
+                    "{}{}",
+                    style(format!("No file matches '{}'", self.filter)).with(Color::Red),
+                    reset_display()
+                ));
             }
 
-            buffer.set_next_cursor((0, y_offset));
+            // The filter, or where the list is scrolled to when it doesn't all fit.
+            let cursor = if self.filtering || !self.filter.is_empty() {
+                buffer.push_line(format!(
+                    "/{}{}",
+                    style(&self.filter).with(crate::color::theme_user_input()),
+                    reset_display()
+                ));
+                Some((
+                    1 + crate::string::len(&self.filter) as u16,
+                    buffer.lines() - 1,
+                ))
+                .filter(|_| self.filtering)
+            } else {
+                if rows.len() + 1 > page {
+                    let status = format!(
+                        "{}-{} of {} files (PgUp/PgDn and Home/End scroll, '/' filters)",
+                        self.scroll.max(1),
+                        (self.scroll + page - 1).min(rows.len()),
+                        rows.len()
+                    );
+                    buffer.push_line(format!(
+                        "{}{}",
+                        style(crate::string::split_at(&status, width).0).with(Color::DarkGrey),
+                        reset_display()
+                    ));
+                }
+                None
+            };
+
+            buffer.set_next_cursor(cursor.unwrap_or((0, y_offset)));
             buffer.render_frame();
             buffer.flush();
         }
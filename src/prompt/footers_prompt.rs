@@ -0,0 +1,137 @@
+use crate::color::reset_display;
+use crate::commitlint::Footer;
+use crate::string::{self, to_byte_offset, to_byte_range};
+use crate::TermBuffer;
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
+    style::{style, Color},
+};
+
+/// Adds footers one line at a time. A bare issue number like `#123` is short for
+/// `Refs #123`.
+#[derive(Debug, Default)]
+pub struct FootersPrompt {
+    footers: Vec<Footer>,
+    input: String,
+    x_offset: u16,
+    error: Option<String>,
+}
+
+pub enum FootersPromptResult {
+    /// Every footer, including the ones the prompt started with.
+    Footers(Vec<Footer>),
+    Escape,
+    Terminate,
+}
+
+impl FootersPrompt {
+    pub fn new(footers: Vec<Footer>) -> Self {
+        FootersPrompt {
+            footers,
+            ..Default::default()
+        }
+    }
+
+    fn parse_input(&self) -> Result<Footer, String> {
+        let input = self.input.trim();
+        if input.starts_with('#') {
+            format!("Refs {}", input).parse()
+        } else {
+            input.parse()
+        }
+    }
+
+    pub fn run(mut self) -> FootersPromptResult {
+        let mut buffer = TermBuffer::new();
+
+        let mut first_iteration = true;
+
+        loop {
+            let event = if first_iteration {
+                first_iteration = false;
+                None
+            } else {
+                match event::read() {
+                    Ok(Event::Key(KeyEvent { code, modifiers })) => Some((
+                        code,
+                        modifiers.contains(KeyModifiers::CONTROL),
+                        modifiers.contains(KeyModifiers::SHIFT),
+                        modifiers.contains(KeyModifiers::ALT),
+                    )),
+                    _ => continue,
+                }
+            };
+
+            match event {
+                Some((KeyCode::Char('c'), true, false, false)) => {
+                    return FootersPromptResult::Terminate;
+                }
+                Some((KeyCode::Esc, _, _, _)) => {
+                    return FootersPromptResult::Escape;
+                }
+                Some((KeyCode::Enter, _, _, _)) if self.input.trim().is_empty() => {
+                    return FootersPromptResult::Footers(self.footers);
+                }
+                Some((KeyCode::Enter, _, _, _)) => match self.parse_input() {
+                    Ok(footer) => {
+                        self.footers.push(footer);
+                        self.input.clear();
+                        self.x_offset = 0;
+                        self.error = None;
+                    }
+                    Err(err) => self.error = Some(err),
+                },
+                // Backspace on an empty line brings back the last footer for editing.
+                Some((KeyCode::Backspace, false, _, false)) if self.input.is_empty() => {
+                    if let Some(footer) = self.footers.pop() {
+                        self.input = footer.to_string();
+                        self.x_offset = string::len(&self.input) as u16;
+                    }
+                }
+                Some((KeyCode::Backspace, false, _, false)) if self.x_offset > 0 => {
+                    let range = to_byte_range(&self.input, self.x_offset as usize - 1);
+                    self.input.replace_range(range, "");
+                    self.x_offset -= 1;
+                }
+                Some((KeyCode::Char(c), false, _, false)) if c > '\x1F' => {
+                    let offset = to_byte_offset(&self.input, self.x_offset as usize);
+                    self.input.insert(offset, c);
+                    self.x_offset += 1;
+                }
+                Some((KeyCode::Left, false, _, false)) => {
+                    self.x_offset = self.x_offset.saturating_sub(1);
+                }
+                Some((KeyCode::Right, false, _, false)) => {
+                    if (self.x_offset as usize) < string::len(&self.input) {
+                        self.x_offset += 1;
+                    }
+                }
+                None => {}
+                _ => continue,
+            };
+
+            buffer.push_line("Footers, e.g. 'Refs #123' or 'Reviewed-by: Name' (enter on an empty line to finish):");
+            for footer in self.footers.iter() {
+                buffer.push_line(format!(
+                    "{}{}",
+                    style(footer.to_string()).with(crate::color::theme_user_input()),
+                    reset_display()
+                ));
+            }
+
+            let input_y = buffer.lines();
+            buffer.push_line(self.input.clone());
+            if let Some(ref error) = self.error {
+                buffer.push_line(format!(
+                    "{}{}",
+                    style(error).with(Color::Red),
+                    reset_display()
+                ));
+            }
+
+            buffer.set_next_cursor((self.x_offset, input_y));
+            buffer.render_frame();
+            buffer.flush();
+        }
+    }
+}
@@ -0,0 +1,248 @@
+use crate::color::reset_display;
+use crate::diff::{FileDiff, LineKind};
+use crate::string;
+use crate::TermBuffer;
+use crossterm::{
+    event::KeyCode,
+    style::{style, Color},
+};
+
+/// Chooses the hunks, or single lines, of a file's diff to commit. Shown by `FilesPrompt`
+/// in place of its file list.
+#[derive(Debug, Clone)]
+pub struct HunkView {
+    pub diff: FileDiff,
+    /// A flag per line of each hunk. Only those of changed lines matter.
+    pub selected: Vec<Vec<bool>>,
+    focused_hunk: usize,
+    /// The focused line of the hunk, when choosing lines rather than hunks.
+    focused_line: Option<usize>,
+}
+
+pub enum HunkViewAction {
+    Done,
+    Cancel,
+}
+
+/// How much of the diff is selected.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Selection {
+    None,
+    Some,
+    All,
+}
+
+impl HunkView {
+    pub fn new(diff: FileDiff, select_all: bool) -> Self {
+        let selected = diff
+            .hunks
+            .iter()
+            .map(|hunk| vec![select_all; hunk.lines.len()])
+            .collect();
+
+        HunkView {
+            diff,
+            selected,
+            focused_hunk: 0,
+            focused_line: None,
+        }
+    }
+
+    /// The patch staging the selected changes, if any are.
+    pub fn patch(&self) -> Option<String> {
+        self.diff.patch(&self.selected)
+    }
+
+    pub fn selection(&self) -> Selection {
+        self.hunk_selection(0..self.diff.hunks.len())
+    }
+
+    fn hunk_selection(&self, hunks: impl Iterator<Item = usize>) -> Selection {
+        let (mut any, mut all) = (false, true);
+        for h in hunks {
+            for (l, line) in self.diff.hunks[h].lines.iter().enumerate() {
+                if line.is_change() {
+                    any |= self.selected[h][l];
+                    all &= self.selected[h][l];
+                }
+            }
+        }
+
+        match (any, all) {
+            (false, _) => Selection::None,
+            (true, true) => Selection::All,
+            (true, false) => Selection::Some,
+        }
+    }
+
+    /// The indices of the changed lines in the focused hunk.
+    fn changes(&self) -> Vec<usize> {
+        match self.diff.hunks.get(self.focused_hunk) {
+            Some(hunk) => (0..hunk.lines.len())
+                .filter(|&l| hunk.lines[l].is_change())
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    fn set_hunk(&mut self, h: usize, value: bool) {
+        for flag in self.selected[h].iter_mut() {
+            *flag = value;
+        }
+    }
+
+    pub fn handle_key(&mut self, code: KeyCode) -> Option<HunkViewAction> {
+        let hunks = self.diff.hunks.len();
+
+        match code {
+            KeyCode::Enter => return Some(HunkViewAction::Done),
+            KeyCode::Esc | KeyCode::Char('q') => return Some(HunkViewAction::Cancel),
+            _ if hunks == 0 => {}
+            KeyCode::Char(' ') => match self.focused_line {
+                Some(l) => {
+                    let flag = &mut self.selected[self.focused_hunk][l];
+                    *flag = !*flag;
+                }
+                None => {
+                    let h = self.focused_hunk;
+                    let value = self.hunk_selection(h..h + 1) != Selection::All;
+                    self.set_hunk(h, value);
+                }
+            },
+            KeyCode::Char('a') => {
+                let value = self.selection() != Selection::All;
+                for h in 0..hunks {
+                    self.set_hunk(h, value);
+                }
+            }
+            KeyCode::Right | KeyCode::Char('l') if self.focused_line.is_none() => {
+                self.focused_line = self.changes().first().copied();
+            }
+            KeyCode::Left | KeyCode::Char('h') => {
+                self.focused_line = None;
+            }
+            KeyCode::Up | KeyCode::Char('k') => match self.focused_line {
+                Some(l) => {
+                    let changes = self.changes();
+                    if let Some(&prev) = changes.iter().rev().find(|&&c| c < l) {
+                        self.focused_line = Some(prev);
+                    }
+                }
+                None => self.focused_hunk = self.focused_hunk.saturating_sub(1),
+            },
+            KeyCode::Down | KeyCode::Char('j') => match self.focused_line {
+                Some(l) => {
+                    let changes = self.changes();
+                    if let Some(&next) = changes.iter().find(|&&c| c > l) {
+                        self.focused_line = Some(next);
+                    }
+                }
+                None => self.focused_hunk = (self.focused_hunk + 1).min(hunks - 1),
+            },
+            _ => {}
+        }
+
+        None
+    }
+
+    /// Pushes the view onto `buffer`, using at most `height` lines and scrolling to keep
+    /// the focus visible. Returns the line of the focus.
+    pub fn render(&self, buffer: &mut TermBuffer, width: usize, height: usize) -> u16 {
+        let title = format!("Choose the changes to commit in {}:", self.diff.path());
+        let help = "<space> toggles, → chooses lines, 'a' toggles all, Enter confirms, Esc cancels";
+        buffer.push_line(title.as_str());
+        buffer.push_line(format!(
+            "{}{}",
+            style(string::split_at(help, width).0).with(Color::DarkGrey),
+            reset_display()
+        ));
+        buffer.push_line(format!("{}{}", "-".repeat(title.len()), reset_display()));
+
+        let mut rows: Vec<String> = vec![];
+        let mut focus = 0;
+
+        if self.diff.hunks.is_empty() {
+            rows.push("There are no lines to choose from, e.g. because the file is binary.".into());
+        }
+
+        for (h, hunk) in self.diff.hunks.iter().enumerate() {
+            let hunk_focused = h == self.focused_hunk;
+            if hunk_focused && self.focused_line.is_none() {
+                focus = rows.len();
+            }
+
+            let check = match self.hunk_selection(h..h + 1) {
+                Selection::All => '☑',
+                Selection::Some => '◩',
+                Selection::None => '□',
+            };
+            let color = if hunk_focused && self.focused_line.is_none() {
+                Color::Blue
+            } else {
+                Color::Cyan
+            };
+            let header = format!(
+                "{} @@ -{},{} +{},{} @@ {}",
+                check, hunk.old_start, hunk.old_len, hunk.new_start, hunk.new_len, hunk.section
+            );
+            rows.push(format!(
+                "{}{}",
+                style(string::split_at(&header, width).0).with(color),
+                reset_display()
+            ));
+
+            for (l, line) in hunk.lines.iter().enumerate() {
+                let text = line.text.replace('\t', "    ");
+                let text = string::split_at(&text, width.saturating_sub(5)).0;
+
+                if !line.is_change() {
+                    rows.push(format!(
+                        "     {}{}",
+                        style(text).with(Color::DarkGrey),
+                        reset_display()
+                    ));
+                    continue;
+                }
+
+                let line_focused = hunk_focused && self.focused_line == Some(l);
+                if line_focused {
+                    focus = rows.len();
+                }
+
+                let selected = self.selected[h][l];
+                let (sign, color) = match (line.kind, selected) {
+                    (LineKind::Added, true) => ('+', Color::Green),
+                    (LineKind::Removed, true) => ('-', Color::Red),
+                    (LineKind::Added, false) => ('+', Color::DarkGrey),
+                    _ => ('-', Color::DarkGrey),
+                };
+                let cursor = if line_focused {
+                    style('›').with(Color::Blue)
+                } else {
+                    style(' ')
+                };
+                let check = if selected { '☑' } else { '□' };
+
+                rows.push(format!(
+                    "{} {} {}{}{}",
+                    cursor,
+                    style(check).with(if line_focused { Color::Blue } else { color }),
+                    style(sign).with(color),
+                    style(text).with(color),
+                    reset_display()
+                ));
+            }
+        }
+
+        // Keep the focused row, along with a few after it, on screen.
+        let height = height.saturating_sub(buffer.lines() as usize).max(1);
+        let ahead = 3.min(height - 1);
+        let skip = (focus + ahead + 1).saturating_sub(height);
+        let cursor = buffer.lines() + (focus - skip) as u16;
+        for row in rows.into_iter().skip(skip).take(height) {
+            buffer.push_line(row);
+        }
+
+        cursor
+    }
+}
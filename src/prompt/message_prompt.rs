@@ -1,5 +1,4 @@
 use crate::string::{self, next_word_grapheme, prev_word_grapheme, to_byte_offset, to_byte_range};
-use crate::Config;
 use crate::TermBuffer;
 use crossterm::{
     event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
@@ -7,10 +6,11 @@ use crossterm::{
 };
 
 #[derive(Debug)]
-pub struct MessagePrompt<'a> {
-    config: &'a Config,
+pub struct MessagePrompt {
     input: Vec<String>,
     cursor: (u16, u16),
+    /// Edits the commit body, where Enter starts a new line, instead of the message.
+    body: bool,
 }
 
 pub enum MessagePromptResult {
@@ -19,15 +19,50 @@ pub enum MessagePromptResult {
     Terminate,
 }
 
-impl<'a> MessagePrompt<'a> {
-    pub fn new(config: &'a Config) -> Self {
+impl MessagePrompt {
+    /// A prompt for the message, starting with `initial`.
+    pub fn new(initial: Option<&str>) -> Self {
+        let initial = initial.unwrap_or_default().to_string();
         MessagePrompt {
-            config,
-            input: vec![String::new()],
-            cursor: (0, 0),
+            cursor: (string::len(&initial) as u16, 0),
+            input: vec![initial],
+            body: false,
         }
     }
 
+    /// A prompt for the body, starting with `initial` when stepping back to it.
+    pub fn body(initial: Option<&str>) -> Self {
+        let input: Vec<String> = match initial {
+            Some(initial) => initial.split('\n').map(String::from).collect(),
+            None => vec![String::new()],
+        };
+        let last = input.len() - 1;
+
+        MessagePrompt {
+            cursor: (string::len(&input[last]) as u16, last as u16),
+            input,
+            body: true,
+        }
+    }
+
+    /// In the body, pressing Enter on an empty last line that follows another empty
+    /// line (or on an empty first line) submits it.
+    fn finishes_body(&self) -> bool {
+        let y = self.cursor.1 as usize;
+        let is_empty = |i: usize| self.input[i].trim().is_empty();
+
+        y + 1 == self.input.len() && is_empty(y) && (y == 0 || is_empty(y - 1))
+    }
+
+    /// Moves the text after the cursor to a new line below.
+    fn split_line(&mut self) {
+        let (x, y) = self.cursor;
+        let line = &mut self.input[y as usize];
+        let rest = line.split_off(to_byte_offset(line, x as usize));
+        self.input.insert(y as usize + 1, rest);
+        self.cursor = (0, y + 1);
+    }
+
     pub fn run(mut self) -> MessagePromptResult {
         let mut buffer = TermBuffer::new();
 
@@ -62,20 +97,27 @@ impl<'a> MessagePrompt<'a> {
                         .input
                         .get(y as usize)
                         .expect("ctrl-e unable to find current line");
-                    self.cursor.0 = string::len(&line) as u16;
+                    self.cursor.0 = string::len(line) as u16;
                 }
                 Some((KeyCode::Char('\n'), _, false, true))
                 | Some((KeyCode::Char('\n'), true, false, _)) => {
                     self.input.push(String::new());
                     self.cursor.1 += 1;
                 }
+                Some((KeyCode::Enter, _, _, _)) if self.body => {
+                    if self.finishes_body() {
+                        let body = self.input.join("\n").trim_end().to_string();
+                        return MessagePromptResult::Message(body);
+                    }
+                    self.split_line();
+                }
                 Some((KeyCode::Enter, _, _, _)) => {
                     return MessagePromptResult::Message(self.input.join("\n"));
                 }
                 Some((KeyCode::Char(c), false, _, false)) if c > '\x1F' => {
                     let (x, y) = self.cursor;
                     let line = self.input.get_mut(y as usize).unwrap();
-                    line.insert(to_byte_offset(&line, x as usize), c);
+                    line.insert(to_byte_offset(line, x as usize), c);
                     self.cursor.0 += 1;
                 }
                 Some((KeyCode::Left, false, _, false)) => {
@@ -168,7 +210,11 @@ impl<'a> MessagePrompt<'a> {
             };
 
             let (x, y) = self.cursor;
-            let instructions = "Commit message (arrow keys for multiple lines):";
+            let instructions = if self.body {
+                "Body (finish with two empty lines, or press enter to skip):"
+            } else {
+                "Commit message (arrow keys for multiple lines):"
+            };
             let divider = "-".repeat(instructions.len());
             buffer.push_line(instructions);
             buffer.push_line(divider);
@@ -176,9 +222,11 @@ impl<'a> MessagePrompt<'a> {
             // The offset for where the editor begins, i.e. the number of push_line calls above.
             let editor_y = 2;
 
+            // Past these lengths, the rest of the line is shown in red.
+            let max_len = if self.body { 100 } else { 50 };
             for (i, line) in self.input.iter().enumerate() {
-                if i == 0 && line.len() > 50 {
-                    let (good, bad) = crate::string::split_at(&line, 50);
+                if (i == 0 || self.body) && line.len() > max_len {
+                    let (good, bad) = crate::string::split_at(line, max_len);
                     buffer.push_line(format!(
                         "{}{}{}",
                         good,
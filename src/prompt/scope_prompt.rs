@@ -10,12 +10,19 @@ use crossterm::{
 pub struct ScopePrompt<'a> {
     config: &'a Config,
     input: String,
-    selected_index: u16,
     ty: &'a str,
     x_offset: u16,
     finished: bool,
+    error: Option<String>,
+    /// Ranked scopes to offer. Falls back to the configured scopes when empty.
+    suggestions: Vec<String>,
+    /// The suggestion chosen with the arrow keys, if any.
+    focused_index: Option<usize>,
 }
 
+/// The number of suggestions listed below the input.
+const MAX_SUGGESTIONS: usize = 6;
+
 pub enum ScopePromptResult {
     Scope(Option<String>, usize),
     Escape,
@@ -23,17 +30,79 @@ pub enum ScopePromptResult {
 }
 
 impl<'a> ScopePrompt<'a> {
-    pub fn new(config: &'a Config, ty: &'a str) -> Self {
+    /// A prompt whose input starts as `initial`, e.g. the scope mapped from the changed files.
+    pub fn new(
+        config: &'a Config,
+        ty: &'a str,
+        suggestions: Vec<String>,
+        initial: Option<String>,
+    ) -> Self {
+        let input = initial.unwrap_or_default();
         ScopePrompt {
             config,
-            input: Default::default(),
-            selected_index: 0,
+            x_offset: input.len() as u16,
+            input,
             ty,
-            x_offset: 0,
             finished: false,
+            error: None,
+            suggestions,
+            focused_index: None,
+        }
+    }
+
+    /// The scope currently being typed, i.e. the text after the last separator.
+    fn current_segment(&self) -> &str {
+        let input = &self.input[..self.x_offset as usize];
+        match input.rfind([',', '|']) {
+            Some(i) => &input[i + 1..],
+            None => input,
         }
     }
 
+    /// The suggestions containing the current segment, with those that start with it
+    /// first. Scopes already entered in another segment are left out.
+    fn filter_scopes(&self) -> Vec<&str> {
+        let segment = self.current_segment();
+        let start = self.x_offset as usize - segment.len();
+        let entered: Vec<&str> = self.input[..start]
+            .split([',', '|'])
+            .chain(self.input[self.x_offset as usize..].split([',', '|']))
+            .collect();
+
+        let candidates = if self.suggestions.is_empty() {
+            &self.config.scopes
+        } else {
+            &self.suggestions
+        };
+        let (prefixed, containing): (Vec<&str>, Vec<&str>) = candidates
+            .iter()
+            .map(|scope| scope.as_str())
+            .filter(|scope| scope.contains(segment) && !entered.contains(scope))
+            .partition(|scope| scope.starts_with(segment));
+
+        prefixed.into_iter().chain(containing).collect()
+    }
+
+    /// Replaces the current segment with the focused scope, or the first match.
+    fn complete(&mut self) {
+        let scopes = self.filter_scopes();
+        let completion = match scopes.get(self.focused_index.unwrap_or(0)) {
+            Some(scope) => scope.to_string(),
+            None => return,
+        };
+
+        let end = self.x_offset as usize;
+        let start = end - self.current_segment().len();
+        self.input.replace_range(start..end, &completion);
+        self.x_offset = (start + completion.len()) as u16;
+        self.focused_index = None;
+    }
+
+    /// Returns an error message if the config restricts scopes and the input uses another.
+    fn validate(&self) -> Option<String> {
+        self.config.check_scope(&self.input).err()
+    }
+
     pub fn run(mut self) -> ScopePromptResult {
         let mut buffer = TermBuffer::new();
 
@@ -65,18 +134,37 @@ impl<'a> ScopePrompt<'a> {
                     return ScopePromptResult::Terminate;
                 }
                 Some((KeyCode::Enter, false, false, false)) => {
-                    self.finished = true;
+                    if self.focused_index.is_some() {
+                        self.complete();
+                    }
+                    self.error = self.validate();
+                    self.finished = self.error.is_none();
+                }
+                Some((KeyCode::Tab, false, false, false)) => {
+                    self.complete();
+                }
+                Some((KeyCode::Down, false, _, false)) => {
+                    let last = self.filter_scopes().len().min(MAX_SUGGESTIONS);
+                    if last > 0 {
+                        let next = self.focused_index.map_or(0, |i| i + 1);
+                        self.focused_index = Some(next.min(last - 1));
+                    }
+                }
+                Some((KeyCode::Up, false, _, false)) => {
+                    self.focused_index = match self.focused_index {
+                        Some(0) | None => None,
+                        Some(i) => Some(i - 1),
+                    };
                 }
                 Some((KeyCode::Char(c), false, _, false)) => {
-                    let accept = (c >= 'a' && c <= 'z')
-                        || (c >= 'A' && c <= 'Z')
-                        || (c >= '0' && c <= '9')
+                    let accept = c.is_ascii_alphanumeric()
                         || (c == '_')
                         || c == '-'
                         || c == '/'
                         || c == ','
                         || c == '|';
                     if accept {
+                        self.focused_index = None;
                         self.x_offset += 1;
 
                         self.input
@@ -92,6 +180,7 @@ impl<'a> ScopePrompt<'a> {
                     }
                 }
                 Some((KeyCode::Backspace, false, _, false)) => {
+                    self.focused_index = None;
                     let offset = self.x_offset as usize;
                     let len = self.input.len();
                     if len > 0 && offset < len - 1 {
@@ -114,7 +203,7 @@ impl<'a> ScopePrompt<'a> {
             let mut lines = figlet.create_vec();
 
             let mut cursor_x = 0;
-            cursor_x += figlet.write_to_buf_color(&self.ty, &mut lines[..], |s| {
+            cursor_x += figlet.write_to_buf_color(self.ty, &mut lines[..], |s| {
                 style(s).with(Color::Blue).to_string()
             });
 
@@ -190,6 +279,32 @@ impl<'a> ScopePrompt<'a> {
                 buffer.push_line(line);
             }
 
+            let scopes = self.filter_scopes();
+            if !self.finished && !scopes.is_empty() {
+                buffer.push_line(format!(
+                    "{}{}",
+                    style("Scopes (tab to complete, arrow keys to choose):").with(Color::Grey),
+                    crate::color::reset_display()
+                ));
+
+                for (i, scope) in scopes.into_iter().take(MAX_SUGGESTIONS).enumerate() {
+                    let line = if Some(i) == self.focused_index {
+                        style(format!("* {}", scope)).with(Color::Blue)
+                    } else {
+                        style(format!("- {}", scope)).with(Color::Green)
+                    };
+                    buffer.push_line(format!("{}{}", line, crate::color::reset_display()));
+                }
+            }
+
+            if let Some(ref error) = self.error {
+                buffer.push_line(format!(
+                    "{}{}",
+                    style(error).with(Color::Red),
+                    crate::color::reset_display()
+                ));
+            }
+
             buffer.set_next_cursor((cursor_x as u16, cursor_y));
             buffer.render_frame();
             buffer.flush();
@@ -1,16 +1,29 @@
 use crate::color::reset_display;
-use crate::Config;
+use crate::fuzzy::fuzzy_match;
 use crate::TermBuffer;
+use crate::{CommitType, Config};
 use crossterm::{
     event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
-    style::{style, Color},
+    style::{style, Color, Styler},
 };
+use std::cmp::Reverse;
 
 #[derive(Debug)]
 pub struct TypePrompt<'a> {
     config: &'a Config,
     input: String,
+    /// Whether the input was typed, rather than only pre-filled.
+    typed: bool,
     focused_index: u16,
+    error: Option<String>,
+}
+
+/// A type matching the input, with the char indices that matched in its name or else
+/// its description.
+struct TypeMatch<'a> {
+    ty: &'a CommitType,
+    name: Vec<usize>,
+    description: Vec<usize>,
 }
 
 pub enum TypePromptResult {
@@ -20,39 +33,99 @@ pub enum TypePromptResult {
 }
 
 impl<'a> TypePrompt<'a> {
-    pub fn new(config: &'a Config) -> Self {
+    /// A prompt whose input starts as `initial`, e.g. the type of a message being edited.
+    pub fn new(config: &'a Config, initial: Option<&str>) -> Self {
         TypePrompt {
             config,
-            input: Default::default(),
+            input: initial.unwrap_or_default().to_string(),
+            typed: false,
             focused_index: 0,
+            error: None,
         }
     }
 
-    /// Attempts to find the item at `self.selected_index`. If greater than
-    /// the number of items, then the last item, or finally falling back to "misc" which
-    /// doesn't normally appear in commitlint.
-    fn get_at_selected_index(&self) -> &str {
+    /// The type at `self.focused_index`, which may be the custom type after the matches.
+    /// If greater than the number of options, then the last one. Fails when nothing
+    /// matches, or the custom type isn't allowed.
+    fn get_at_selected_index(&self) -> Result<String, String> {
         let options = self.filter_types();
-        options
-            .get(self.focused_index as usize)
-            .or_else(|| options.last())
-            .copied()
-            .unwrap_or("misc")
+        if let Some(m) = options.get(self.focused_index as usize) {
+            return Ok(m.ty.name.clone());
+        }
+
+        match (self.custom_type(), options.last()) {
+            (Some(ty), _) => self.config.check_type(ty).map(|_| ty.to_string()),
+            (None, Some(m)) => Ok(m.ty.name.clone()),
+            (None, None) => Err(format!("No type matches '{}'", self.input)),
+        }
+    }
+
+    /// The input, when it could be used as a new type. Matching ignores case, so input
+    /// that only differs in case from a configured type picks that type instead.
+    fn custom_type(&self) -> Option<&str> {
+        let configured = |input: &str| {
+            self.config
+                .types
+                .iter()
+                .any(|ty| ty.name.eq_ignore_ascii_case(input))
+        };
+
+        Some(self.input.as_str())
+            .filter(|input| self.config.custom_types && !input.is_empty() && !configured(input))
+    }
+
+    /// The type chosen without pressing Enter, when it's the only one matching what was
+    /// typed. Pre-filled input never submits, so that the type can still be changed.
+    fn auto_submitted(&self) -> Option<String> {
+        if !self.typed || !self.config.auto_submit_type || self.custom_type().is_some() {
+            return None;
+        }
+
+        match self.filter_types().as_slice() {
+            [m] => Some(m.ty.name.clone()),
+            _ => None,
+        }
+    }
+
+    /// Changes the input, as typed by the user.
+    fn edit_input(&mut self, edit: impl FnOnce(&mut String)) {
+        edit(&mut self.input);
+        self.typed = true;
+        self.focused_index = 0;
+        self.error = None;
     }
 
-    fn filter_types(&self) -> Vec<&str> {
-        self.config
+    /// The types whose name fuzzy matches the input, best first, followed by those whose
+    /// description matches well.
+    fn filter_types(&self) -> Vec<TypeMatch<'_>> {
+        let mut scored: Vec<(bool, i64, TypeMatch)> = self
+            .config
             .types
             .iter()
-            .map(|x| x.as_str())
-            .filter(|item| {
-                if self.input.is_empty() {
-                    true
-                } else {
-                    item.starts_with(&self.input)
+            .filter_map(|ty| {
+                if let Some(m) = fuzzy_match(&self.input, &ty.name) {
+                    let name = TypeMatch {
+                        ty,
+                        name: m.indices,
+                        description: vec![],
+                    };
+                    return Some((true, m.score, name));
                 }
+
+                let m =
+                    fuzzy_match(&self.input, ty.description.as_deref()?).filter(|m| m.score > 0)?;
+                let description = TypeMatch {
+                    ty,
+                    name: vec![],
+                    description: m.indices,
+                };
+                Some((false, m.score, description))
             })
-            .collect()
+            .collect();
+
+        // Stable, so equally good matches keep the configured order.
+        scored.sort_by_key(|&(name, score, _)| Reverse((name, score)));
+        scored.into_iter().map(|(_, _, m)| m).collect()
     }
 
     pub fn run(mut self) -> TypePromptResult {
@@ -84,14 +157,17 @@ impl<'a> TypePrompt<'a> {
                 Some((KeyCode::Char('c'), true, false, false)) => {
                     return TypePromptResult::Terminate;
                 }
-                Some((KeyCode::Enter, false, false, false)) => {
-                    return TypePromptResult::Type(self.get_at_selected_index().to_string());
-                }
+                Some((KeyCode::Enter, false, false, false)) => match self.get_at_selected_index() {
+                    Ok(ty) => return TypePromptResult::Type(ty),
+                    Err(err) => self.error = Some(err),
+                },
                 Some((KeyCode::Char(c), false, _, false)) => {
-                    self.input.push(c.to_ascii_lowercase());
+                    self.edit_input(|input| input.push(c));
                 }
                 Some((KeyCode::Backspace, false, _, false)) => {
-                    self.input.pop();
+                    self.edit_input(|input| {
+                        input.pop();
+                    });
                 }
                 Some((KeyCode::Esc, false, _, false)) => {
                     return TypePromptResult::Escape;
@@ -100,7 +176,8 @@ impl<'a> TypePrompt<'a> {
                     self.focused_index = self.focused_index.saturating_sub(1);
                 }
                 Some((KeyCode::Down, false, _, false)) => {
-                    let total = self.config.types.len() as u16;
+                    let total =
+                        (self.filter_types().len() + self.custom_type().iter().count()) as u16;
 
                     self.focused_index += 1;
                     if self.focused_index >= total {
@@ -111,10 +188,10 @@ impl<'a> TypePrompt<'a> {
                 _ => continue,
             };
 
-            let types = self.filter_types();
-            if types.len() == 1 {
-                return TypePromptResult::Type(types[0].to_string());
+            if let Some(ty) = self.auto_submitted() {
+                return TypePromptResult::Type(ty);
             }
+            let types = self.filter_types();
 
             let mut header = figlet.create_vec();
             figlet.write_to_buf_color("<glint>", header.as_mut_slice(), |s| {
@@ -146,20 +223,97 @@ impl<'a> TypePrompt<'a> {
             let focused_color = Color::Blue;
             let default_color = Color::Reset;
 
-            for (i, ty) in types.into_iter().enumerate() {
-                let line_content = if i as u16 == self.focused_index {
-                    style(["*", " ", ty].concat()).with(focused_color)
+            // Descriptions line up after the longest name.
+            let width = types.iter().map(|m| m.ty.name.len()).max().unwrap_or(0);
+
+            let custom_index = types.len();
+            for (i, m) in types.into_iter().enumerate() {
+                let (marker, color) = if i as u16 == self.focused_index {
+                    ("*", focused_color)
                 } else {
-                    style(["-", " ", ty].concat()).with(default_color)
+                    ("-", m.ty.color.unwrap_or(default_color))
                 };
+                let name = &m.ty.name;
+                let description = m.ty.description.as_deref().unwrap_or("");
 
-                let line = format!("{}{}", line_content, reset_display());
+                let line = format!(
+                    "{} {}{}  {}{}",
+                    style(marker).with(color),
+                    highlight(name, &m.name, color),
+                    " ".repeat(width - name.len()),
+                    highlight(description, &m.description, Color::DarkGrey),
+                    reset_display()
+                );
                 buffer.push_line(line);
             }
 
+            if let Some(ty) = self.custom_type() {
+                let line = if self.focused_index as usize >= custom_index {
+                    style(format!("* use '{}' as a new type", ty)).with(focused_color)
+                } else {
+                    style(format!("+ use '{}' as a new type", ty)).with(Color::Green)
+                };
+                buffer.push_line(format!("{}{}", line, reset_display()));
+            }
+
+            if let Some(ref error) = self.error {
+                buffer.push_line(format!(
+                    "{}{}",
+                    style(error).with(Color::Red),
+                    reset_display()
+                ));
+            }
+
             buffer.set_next_cursor((after_prompt_x, y_offset));
             buffer.render_frame();
             buffer.flush();
         }
     }
 }
+
+/// Renders `text` in `color`, underlining the chars at `indices`.
+fn highlight(text: &str, indices: &[usize], color: Color) -> String {
+    text.chars()
+        .enumerate()
+        .map(|(i, c)| {
+            if indices.contains(&i) {
+                style(c).with(color).underlined().to_string()
+            } else {
+                style(c).with(color).to_string()
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::TypePrompt;
+    use crate::Config;
+
+    #[test]
+    fn auto_submits_only_typed_input() {
+        let config = Config::default();
+
+        // The type of an amended commit stays editable.
+        let mut prompt = TypePrompt::new(&config, Some("fix"));
+        assert_eq!(prompt.auto_submitted(), None);
+
+        prompt.edit_input(|input| input.clear());
+        assert_eq!(prompt.auto_submitted(), None);
+
+        prompt.edit_input(|input| input.push_str("fix"));
+        assert_eq!(prompt.auto_submitted().as_deref(), Some("fix"));
+    }
+
+    #[test]
+    fn auto_submit_can_be_turned_off() {
+        let config = Config {
+            auto_submit_type: false,
+            ..Config::default()
+        };
+        let mut prompt = TypePrompt::new(&config, None);
+
+        prompt.edit_input(|input| input.push_str("fix"));
+        assert_eq!(prompt.auto_submitted(), None);
+    }
+}
@@ -0,0 +1,208 @@
+//! Ranks the scopes offered by the Scope prompt.
+use crate::git::LogItem;
+use crate::glob;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Directories that hold the interesting ones rather than naming a part of the project.
+const CONTAINER_DIRS: &[&str] = &[
+    "src", "lib", "libs", "packages", "crates", "apps", "app", "pkg", "internal", "cmd", "source",
+    "modules",
+];
+
+/// The weight of a scope used this many days before the newest commit halves.
+const HALF_LIFE_DAYS: f64 = 30.0;
+
+/// Scopes inferred from the changed files count for more than any amount of history.
+const PATH_WEIGHT: f64 = 100.0;
+
+/// Maps the files matching `glob` to `scope`, e.g. `packages/client/**` to `client`.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScopePath {
+    /// Matched against the whole path from the repo root, see `glob::matches_path`.
+    pub glob: String,
+    pub scope: String,
+}
+
+impl ScopePath {
+    pub fn matches(&self, path: &str) -> bool {
+        glob::matches_path(&self.glob, path)
+    }
+}
+
+/// The scope of the first mapping matching `path`.
+pub fn mapped_scope<'a>(mappings: &'a [ScopePath], path: &str) -> Option<&'a str> {
+    mappings
+        .iter()
+        .find(|mapping| mapping.matches(path))
+        .map(|mapping| mapping.scope.as_str())
+}
+
+/// The scopes mapped from `files`, in the order they first appear, or None unless every
+/// file is mapped.
+pub fn mapped_scopes<'a>(mappings: &'a [ScopePath], files: &[String]) -> Option<Vec<&'a str>> {
+    if files.is_empty() {
+        return None;
+    }
+
+    let mut scopes: Vec<&str> = vec![];
+    for file in files {
+        let scope = mapped_scope(mappings, file)?;
+        if !scopes.contains(&scope) {
+            scopes.push(scope);
+        }
+    }
+
+    Some(scopes)
+}
+
+/// Guesses the scope of a file from its directories, skipping containers like `src`,
+/// e.g. `packages/client/src/api.ts` → `client`.
+pub fn scope_for_path(path: &str) -> Option<&str> {
+    let mut dirs: Vec<&str> = path.trim_end_matches('/').split('/').collect();
+    dirs.pop();
+
+    dirs.into_iter()
+        .find(|dir| !dir.starts_with('.') && !CONTAINER_DIRS.contains(dir))
+}
+
+/// Ranks the scopes used in `logs` (newest first) by how often and how recently they
+/// were used, after the scopes of the changed `files`, taken from `mappings` or else
+/// guessed from their directories. When `allowed` is non-empty, only those scopes are
+/// suggested, and all of them are.
+pub fn suggest(
+    logs: &[LogItem],
+    files: &[String],
+    mappings: &[ScopePath],
+    allowed: &[String],
+) -> Vec<String> {
+    let mut scores: HashMap<String, f64> = HashMap::new();
+
+    let newest = logs.first().map_or(0, |log| log.epoch_secs);
+    for log in logs {
+        let commit = match log.as_conventional() {
+            Ok(commit) => commit,
+            Err(_) => continue,
+        };
+
+        let days = (newest - log.epoch_secs).max(0) as f64 / 86_400.0;
+        let weight = 0.5_f64.powf(days / HALF_LIFE_DAYS);
+        for scope in commit.scope.into_iter().flat_map(|s| s.split([',', '|'])) {
+            let scope = scope.trim();
+            if !scope.is_empty() {
+                *scores.entry(scope.to_string()).or_default() += weight;
+            }
+        }
+    }
+
+    for file in files {
+        let scope = match mapped_scope(mappings, file).or_else(|| scope_for_path(file)) {
+            Some(scope) => scope,
+            None => continue,
+        };
+        *scores.entry(scope.to_string()).or_default() += PATH_WEIGHT;
+    }
+
+    if !allowed.is_empty() {
+        scores.retain(|scope, _| allowed.contains(scope));
+        for scope in allowed {
+            scores.entry(scope.clone()).or_default();
+        }
+    }
+
+    let mut ranked: Vec<(String, f64)> = scores.into_iter().collect();
+    ranked.sort_by(|(a, a_score), (b, b_score)| b_score.total_cmp(a_score).then_with(|| a.cmp(b)));
+    ranked.into_iter().map(|(scope, _)| scope).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{mapped_scopes, scope_for_path, suggest, ScopePath};
+    use crate::git::LogItem;
+
+    fn log(days_ago: i64, message: &str) -> LogItem {
+        LogItem {
+            commit: String::new(),
+            epoch_secs: 1_000_000_000 - days_ago * 86_400,
+            message: message.to_string(),
+            files: vec![],
+        }
+    }
+
+    #[test]
+    fn paths() {
+        assert_eq!(scope_for_path("packages/client/src/api.ts"), Some("client"));
+        assert_eq!(scope_for_path("src/git/parse_log.rs"), Some("git"));
+        assert_eq!(
+            scope_for_path(".github/workflows/ci.yml"),
+            Some("workflows")
+        );
+        assert_eq!(scope_for_path("src/main.rs"), None);
+        assert_eq!(scope_for_path("README.md"), None);
+    }
+
+    #[test]
+    fn ranks_by_recency_then_paths() {
+        let logs = vec![
+            log(0, "fix(client): a"),
+            log(1, "feat(server,client): b"),
+            log(200, "fix(docs): c"),
+            log(201, "fix(docs): d"),
+            log(202, "chore: e"),
+        ];
+
+        assert_eq!(
+            suggest(&logs, &[], &[], &[]),
+            vec!["client", "server", "docs"]
+        );
+
+        let files = vec!["src/prompt/scope_prompt.rs".to_string()];
+        assert_eq!(
+            suggest(&logs, &files, &[], &[]),
+            vec!["prompt", "client", "server", "docs"]
+        );
+
+        let allowed = vec!["docs".to_string(), "web".to_string()];
+        assert_eq!(suggest(&logs, &files, &[], &allowed), vec!["docs", "web"]);
+    }
+
+    #[test]
+    fn mappings() {
+        let mapping = |glob: &str, scope: &str| ScopePath {
+            glob: glob.to_string(),
+            scope: scope.to_string(),
+        };
+        let mappings = vec![
+            mapping("packages/client/**", "client"),
+            mapping("packages/*-server/**", "server"),
+            mapping("**/*.md", "docs"),
+        ];
+        let files = |files: &[&str]| files.iter().map(|f| f.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            mapped_scopes(
+                &mappings,
+                &files(&["packages/client/a.ts", "packages/client/b.ts"])
+            ),
+            Some(vec!["client"])
+        );
+        assert_eq!(
+            mapped_scopes(
+                &mappings,
+                &files(&["packages/client/a.ts", "docs/guide.md"])
+            ),
+            Some(vec!["client", "docs"])
+        );
+        assert_eq!(
+            mapped_scopes(&mappings, &files(&["packages/client/a.ts", "Cargo.toml"])),
+            None
+        );
+
+        let logs = vec![log(0, "fix(web): a")];
+        assert_eq!(
+            suggest(&logs, &files(&["packages/client/a.ts"]), &mappings, &[]),
+            vec!["client", "web"]
+        );
+    }
+}
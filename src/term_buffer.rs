@@ -246,11 +246,10 @@ impl TermBuffer {
 }
 
 /// Represents internal state of TermBuffer
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 struct State {
     cursor: (u16, u16),
     rows: Vec<String>,
-    first_row: u16,
 }
 
 impl PartialEq for State {
@@ -259,16 +258,6 @@ impl PartialEq for State {
     }
 }
 
-impl Default for State {
-    fn default() -> Self {
-        State {
-            cursor: (0, 0),
-            rows: vec![],
-            first_row: 0,
-        }
-    }
-}
-
 impl State {
     pub fn len(&self) -> usize {
         self.rows.len()
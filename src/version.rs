@@ -0,0 +1,351 @@
+//! Semantic versions, and the bump that a set of conventional commits calls for.
+use crate::commitlint::ConventionalCommit;
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    /// Dot separated pre-release identifiers, e.g. `["rc", "1"]`.
+    pub pre: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Bump {
+    Patch,
+    Minor,
+    Major,
+}
+
+impl Bump {
+    /// The bump a single commit calls for: major for breaking changes, minor for features,
+    /// and patch for any other type.
+    pub fn for_commit(commit: &ConventionalCommit) -> Bump {
+        if commit.breaking {
+            Bump::Major
+        } else if commit.ty == "feat" {
+            Bump::Minor
+        } else {
+            Bump::Patch
+        }
+    }
+}
+
+impl Version {
+    pub fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Version {
+            major,
+            minor,
+            patch,
+            pre: vec![],
+        }
+    }
+
+    pub fn is_prerelease(&self) -> bool {
+        !self.pre.is_empty()
+    }
+
+    /// The version without its pre-release identifiers.
+    pub fn release(&self) -> Version {
+        Version::new(self.major, self.minor, self.patch)
+    }
+
+    /// Before 1.0.0 everything may change, so breaking changes only bump the minor
+    /// version and features the patch version.
+    pub fn effective_bump(&self, bump: Bump) -> Bump {
+        match (self.major, bump) {
+            (0, Bump::Major) => Bump::Minor,
+            (0, Bump::Minor) => Bump::Patch,
+            _ => bump,
+        }
+    }
+
+    /// The next version after this one. With `pre`, it's a pre-release of the next
+    /// version, e.g. `1.3.0-rc.0`, and releasing from a pre-release only bumps the
+    /// version if the pre-release doesn't already cover `bump` (`1.3.0-rc.2` → `1.3.0`
+    /// for a minor bump).
+    pub fn bump(&self, bump: Bump, pre: Option<&str>) -> Version {
+        let bump = self.effective_bump(bump);
+        let base = if self.is_prerelease() && self.covers(bump) {
+            self.release()
+        } else {
+            let v = self.release();
+            match bump {
+                Bump::Major => Version::new(v.major + 1, 0, 0),
+                Bump::Minor => Version::new(v.major, v.minor + 1, 0),
+                Bump::Patch => Version::new(v.major, v.minor, v.patch + 1),
+            }
+        };
+
+        let pre = match pre {
+            Some(id) => id,
+            None => return base,
+        };
+
+        // Continue numbering the same pre-release, e.g. rc.1 → rc.2
+        let number = if base == self.release() && self.pre.first().map(String::as_str) == Some(pre)
+        {
+            self.pre
+                .get(1)
+                .and_then(|n| n.parse::<u64>().ok())
+                .map_or(0, |n| n + 1)
+        } else {
+            0
+        };
+
+        Version {
+            pre: vec![pre.to_string(), number.to_string()],
+            ..base
+        }
+    }
+
+    /// Whether this pre-release's version is already as far from the previous release
+    /// as `bump` would move it.
+    fn covers(&self, bump: Bump) -> bool {
+        match bump {
+            Bump::Patch => true,
+            Bump::Minor => self.patch == 0,
+            Bump::Major => self.minor == 0 && self.patch == 0,
+        }
+    }
+}
+
+/// Rewrites the `version` of the `[package]` table in a Cargo.toml, keeping the rest of
+/// the file as it is. Returns `None` if there's no such key.
+pub fn set_cargo_version(manifest: &str, version: &Version) -> Option<String> {
+    let mut in_package = false;
+    let mut offset = 0;
+
+    for line in manifest.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            in_package = trimmed == "[package]";
+        } else if in_package {
+            let value = trimmed
+                .strip_prefix("version")
+                .map(str::trim_start)
+                .and_then(|rest| rest.strip_prefix('='));
+            if let Some(value) = value {
+                let quoted = value.trim();
+                let start = offset + line.find(quoted)?;
+                let end = start + quoted.find('#').unwrap_or(quoted.len());
+                let end = start + manifest[start..end].trim_end().len();
+                return Some(format!(
+                    "{}\"{}\"{}",
+                    &manifest[..start],
+                    version,
+                    &manifest[end..]
+                ));
+            }
+        }
+        offset += line.len();
+    }
+
+    None
+}
+
+/// Rewrites the top-level `version` in a package.json, keeping its formatting. Returns
+/// `None` if there's no such key.
+pub fn set_package_json_version(manifest: &str, version: &Version) -> Option<String> {
+    let json: serde_json::Value = serde_json::from_str(manifest).ok()?;
+    let current = json.get("version")?.as_str()?;
+
+    // Nested objects come after the top-level version in practice, so the first match
+    // is the right one.
+    let key = manifest.find("\"version\"")?;
+    let after_key = key + "\"version\"".len();
+    let colon = after_key + manifest[after_key..].find(':')?;
+    let start = colon + manifest[colon..].find('"')?;
+    let quoted = format!("\"{}\"", current);
+    if !manifest[start..].starts_with(&quoted) {
+        return None;
+    }
+
+    Some(format!(
+        "{}\"{}\"{}",
+        &manifest[..start],
+        version,
+        &manifest[start + quoted.len()..]
+    ))
+}
+
+impl FromStr for Version {
+    type Err = String;
+
+    /// Parses `1.2.3` or `1.2.3-rc.1`, with an optional `v` prefix. Build metadata is
+    /// ignored.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("'{}' isn't a semantic version", s);
+        let version = s.strip_prefix('v').unwrap_or(s);
+        let version = version.split('+').next().unwrap_or(version);
+        let (core, pre) = match version.split_once('-') {
+            Some((core, pre)) => (core, Some(pre)),
+            None => (version, None),
+        };
+
+        let numbers = core
+            .split('.')
+            .map(|n| match n {
+                "" => Err(invalid()),
+                _ if n.len() > 1 && n.starts_with('0') => Err(invalid()),
+                _ => n.parse::<u64>().map_err(|_| invalid()),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let (major, minor, patch) = match numbers[..] {
+            [major, minor, patch] => (major, minor, patch),
+            _ => return Err(invalid()),
+        };
+
+        let pre = match pre {
+            Some(pre) => {
+                let ids: Vec<String> = pre.split('.').map(String::from).collect();
+                let valid = |id: &String| {
+                    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+                };
+                if !ids.iter().all(valid) {
+                    return Err(invalid());
+                }
+                ids
+            }
+            None => vec![],
+        };
+
+        Ok(Version {
+            major,
+            minor,
+            patch,
+            pre,
+        })
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if self.is_prerelease() {
+            write!(f, "-{}", self.pre.join("."))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Bump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Bump::Patch => write!(f, "patch"),
+            Bump::Minor => write!(f, "minor"),
+            Bump::Major => write!(f, "major"),
+        }
+    }
+}
+
+impl Ord for Version {
+    /// Semver precedence: a pre-release sorts before its release, and numeric
+    /// identifiers sort numerically.
+    fn cmp(&self, other: &Self) -> Ordering {
+        let core =
+            (self.major, self.minor, self.patch).cmp(&(other.major, other.minor, other.patch));
+        if core != Ordering::Equal {
+            return core;
+        }
+
+        match (self.pre.is_empty(), other.pre.is_empty()) {
+            (true, true) => return Ordering::Equal,
+            (true, false) => return Ordering::Greater,
+            (false, true) => return Ordering::Less,
+            _ => {}
+        }
+
+        for (a, b) in self.pre.iter().zip(other.pre.iter()) {
+            let ordering = match (a.parse::<u64>(), b.parse::<u64>()) {
+                (Ok(a), Ok(b)) => a.cmp(&b),
+                (Ok(_), Err(_)) => Ordering::Less,
+                (Err(_), Ok(_)) => Ordering::Greater,
+                (Err(_), Err(_)) => a.cmp(b),
+            };
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+
+        self.pre.len().cmp(&other.pre.len())
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{set_cargo_version, set_package_json_version, Bump, Version};
+
+    fn v(s: &str) -> Version {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parse_and_order() {
+        assert_eq!(v("v1.2.3"), Version::new(1, 2, 3));
+        assert_eq!(v("1.2.3-rc.1+build.5").to_string(), "1.2.3-rc.1");
+        assert!("1.2".parse::<Version>().is_err());
+        assert!("01.2.3".parse::<Version>().is_err());
+
+        let mut versions = [
+            v("1.0.0"),
+            v("1.0.0-rc.10"),
+            v("1.0.0-rc.2"),
+            v("1.0.0-alpha"),
+        ];
+        versions.sort();
+        let sorted: Vec<String> = versions.iter().map(Version::to_string).collect();
+        assert_eq!(
+            sorted,
+            vec!["1.0.0-alpha", "1.0.0-rc.2", "1.0.0-rc.10", "1.0.0"]
+        );
+    }
+
+    #[test]
+    fn bumps() {
+        let bump = |from: &str, bump, pre| v(from).bump(bump, pre).to_string();
+
+        assert_eq!(bump("1.2.3", Bump::Major, None), "2.0.0");
+        assert_eq!(bump("1.2.3", Bump::Minor, None), "1.3.0");
+        assert_eq!(bump("1.2.3", Bump::Patch, None), "1.2.4");
+
+        // Pre-1.0
+        assert_eq!(bump("0.4.1", Bump::Major, None), "0.5.0");
+        assert_eq!(bump("0.4.1", Bump::Minor, None), "0.4.2");
+
+        // Pre-releases
+        assert_eq!(bump("1.2.3", Bump::Minor, Some("rc")), "1.3.0-rc.0");
+        assert_eq!(bump("1.3.0-rc.0", Bump::Patch, Some("rc")), "1.3.0-rc.1");
+        assert_eq!(
+            bump("1.3.0-alpha.4", Bump::Minor, Some("beta")),
+            "1.3.0-beta.0"
+        );
+        assert_eq!(bump("1.3.0-rc.1", Bump::Minor, None), "1.3.0");
+        assert_eq!(bump("1.3.0-rc.1", Bump::Major, None), "2.0.0");
+    }
+
+    #[test]
+    fn rewrites_manifests() {
+        let cargo = "[package]\nname = \"x\"\nversion = \"0.1.0\" # bumped by glint\n\n[dependencies]\nversion = \"1\"\n";
+        assert_eq!(
+            set_cargo_version(cargo, &v("0.2.0")).unwrap(),
+            "[package]\nname = \"x\"\nversion = \"0.2.0\" # bumped by glint\n\n[dependencies]\nversion = \"1\"\n"
+        );
+        assert_eq!(set_cargo_version("[workspace]\n", &v("0.2.0")), None);
+
+        let json = "{\n  \"name\": \"x\",\n  \"version\": \"1.0.0\",\n  \"dependencies\": {}\n}\n";
+        assert_eq!(
+            set_package_json_version(json, &v("1.1.0")).unwrap(),
+            "{\n  \"name\": \"x\",\n  \"version\": \"1.1.0\",\n  \"dependencies\": {}\n}\n"
+        );
+    }
+}